@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use num::BigInt;
+
+use crate::schemas::{AbsenceKind, Dated, Term};
+
+/// An absence mark with the day and the subject of the lesson it was put on. The attendance
+/// report does not tell the number of the lesson, so the absences from two lessons of the subject
+/// on the same day are two equal records.
+#[derive(Debug, Clone)]
+pub struct AttendanceRecord {
+    pub kind: AbsenceKind,
+    pub date: NaiveDate,
+    pub subject_name: String,
+}
+
+impl Dated for AttendanceRecord {
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+}
+
+/// Missed lesson counts. Being late is not counted as missing a lesson.
+#[derive(Debug, Default)]
+pub struct AttendanceSummary {
+    /// The subject name to the number of missed lessons.
+    pub by_subject: BTreeMap<String, usize>,
+    /// The term id to the subject name to the number of missed lessons. The lessons that do not
+    /// fall in any of the terms are not counted here.
+    pub by_term: BTreeMap<BigInt, BTreeMap<String, usize>>,
+}
+
+impl AttendanceSummary {
+    pub fn new(records: &[AttendanceRecord], terms: &[Term]) -> Self {
+        let mut summary = Self::default();
+        for record in records.iter().filter(|record| record.kind.is_missed()) {
+            *summary
+                .by_subject
+                .entry(record.subject_name.clone())
+                .or_default() += 1;
+            if let Some(term) = Term::find(terms, record) {
+                *summary
+                    .by_term
+                    .entry(term.id.clone())
+                    .or_default()
+                    .entry(record.subject_name.clone())
+                    .or_default() += 1;
+            }
+        }
+        summary
+    }
+}
@@ -110,3 +110,78 @@ impl Json for serde_json::Value {
         self.as_str()
     }
 }
+
+/// (De)serializes a `BigInt` as a plain number instead of the sign-and-digits tuple `num` uses.
+/// Made to be used with `#[serde(with = "crate::json::big_int")]`.
+pub mod big_int {
+    use std::borrow::Borrow;
+
+    use num::{BigInt, ToPrimitive};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, Number: Borrow<BigInt>>(
+        number: &Number,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let number = number.borrow();
+        match number.to_i64() {
+            Some(number) => serializer.serialize_i64(number),
+            None => serializer.serialize_str(&number.to_string()),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        serde_json::Number::deserialize(deserializer)?
+            .to_string()
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Same as `big_int`, but for `Option<BigInt>`.
+pub mod optional_big_int {
+    use num::BigInt;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super::big_int")] BigInt);
+
+    pub fn serialize<S: Serializer>(
+        number: &Option<BigInt>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match number {
+            Some(number) => super::big_int::serialize(number, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<BigInt>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(number)| number))
+    }
+}
+
+/// Same as `big_int`, but for a sequence of them.
+pub mod big_ints {
+    use std::borrow::Borrow;
+
+    use num::BigInt;
+    use serde::{ser::SerializeSeq, Serializer};
+
+    pub fn serialize<S: Serializer, Numbers: Borrow<[BigInt]>>(
+        numbers: &Numbers,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct Wrapper<'number>(#[serde(with = "super::big_int")] &'number BigInt);
+
+        let numbers = numbers.borrow();
+        let mut sequence = serializer.serialize_seq(Some(numbers.len()))?;
+        for number in numbers {
+            sequence.serialize_element(&Wrapper(number))?;
+        }
+        sequence.end()
+    }
+}
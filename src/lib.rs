@@ -1,6 +1,17 @@
-mod auth_data;
+pub mod attendance;
+pub mod auth_data;
 mod web_clients;
-mod net_school_clients;
+pub mod net_school_clients;
 mod json;
-mod user_data;
-mod schemas;
+pub mod user_data;
+pub mod schemas;
+pub mod reports;
+pub mod timetable_changes;
+mod server_dates;
+
+pub use json::{Json, StrNum};
+pub use web_clients::{
+    logged_out_web_client::LoggedOutWebClient, url_ending_with_a_slash::UrlEndingWithASlash,
+    url_with_api_base_route::UrlWithApiBaseRoute, web_client_with_cookies::WebClientWithCookies,
+    web_client_wrapper::WebClientWrapper,
+};
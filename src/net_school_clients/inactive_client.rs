@@ -5,7 +5,10 @@ use encoding::Encoding;
 
 use crate::{
     auth_data::AuthData,
-    schemas::{AuthRequest, AuthResponse, LoginType, PreAuthData, SchoolInfo},
+    schemas::{
+        AddressLevel, AddressOptionsRequest, AddressOptionsResponse, AddressSelection, AuthRequest,
+        AuthResponse, LoginType, NamedEntity, PreAuthData, SchoolInfo, SchoolSearchRequest,
+    },
     user_data::{self, UserData},
     web_clients::{
         logged_out_web_client::LoggedOutWebClient, web_client_wrapper::WebClientWrapper,
@@ -57,6 +60,117 @@ impl From<SchoolsGettingError> for SchoolByNameGettingError {
     }
 }
 
+/// How many schools `InactiveClient::school_by_name` asks the server for at once.
+const SCHOOLS_BY_NAME_PAGE_SIZE: u32 = 100;
+/// How many pages `InactiveClient::school_by_name` looks through at most for every query. The
+/// results past them are lost, so a query that has more is followed by the next one.
+const SCHOOLS_BY_NAME_MAX_PAGES: u32 = 5;
+
+/// The beginnings of the words most school names have (the legal form, the "school" word and so
+/// on). Searching by them finds almost every school.
+const COMMON_SCHOOL_NAME_WORDS: [&str; 11] = [
+    "муниципальн",
+    "государственн",
+    "бюджетн",
+    "автономн",
+    "казенн",
+    "казённ",
+    "общеобразовательн",
+    "образовательн",
+    "учреждени",
+    "средн",
+    "школ",
+];
+
+/// The abbreviations of the legal forms and the school kinds, common to most school names as well.
+const COMMON_SCHOOL_NAME_ABBREVIATIONS: [&str; 7] =
+    ["мбоу", "маоу", "гбоу", "мкоу", "сош", "оош", "цо"];
+
+fn school_name_words(name: &str) -> impl Iterator<Item = &str> {
+    name.split(|character: char| {
+        character.is_whitespace() || matches!(character, '"' | '«' | '»' | '\'' | '№')
+    })
+    .filter(|word| !word.is_empty())
+}
+
+/// Makes names that differ only in case, spacing or quotes equal.
+fn normalize_school_name(name: &str) -> String {
+    school_name_words(name)
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_common_school_name_word(word: &str) -> bool {
+    let word = word.to_lowercase();
+    COMMON_SCHOOL_NAME_ABBREVIATIONS.contains(&word.as_str())
+        || COMMON_SCHOOL_NAME_WORDS
+            .iter()
+            .any(|common_word| word.starts_with(common_word))
+}
+
+fn is_school_number(word: &str) -> bool {
+    word.chars().all(|character| character.is_ascii_digit())
+}
+
+/// Makes the queries to search the schools by on the server, grouped by the word of the name they
+/// are made of. The most specific words go first: the ones that are not common to most school
+/// names, the longest first, then the numbers and then the common words. A bare number would find
+/// every school with the digit anywhere in its name, so a number is searched after the "№" sign,
+/// with and without a space.
+fn school_search_queries(name: &str) -> Vec<Vec<String>> {
+    let mut words: Vec<&str> = school_name_words(name).collect();
+    words.sort_by_key(|word| {
+        let kind = if is_school_number(word) {
+            1
+        } else if is_common_school_name_word(word) {
+            2
+        } else {
+            0
+        };
+        (
+            kind,
+            std::cmp::Reverse(word.chars().count()),
+            word.to_lowercase(),
+        )
+    });
+    words.dedup_by(|word, previous_word| word.to_lowercase() == previous_word.to_lowercase());
+    words
+        .into_iter()
+        .map(|word| {
+            if is_school_number(word) {
+                vec![format!("№{}", word), format!("№ {}", word)]
+            } else {
+                vec![word.to_owned()]
+            }
+        })
+        .collect()
+}
+
+/// Lower is better: the exact match comes first, then the names having every searched word,
+/// the ones with fewer extra words first. `None` if some searched word is not in the school name.
+fn school_name_rank(searched_name: &str, school_name: &str) -> Option<(u8, usize)> {
+    if searched_name.is_empty() {
+        return None;
+    }
+    if school_name == searched_name {
+        return Some((0, 0));
+    }
+    let school_words: Vec<&str> = school_name.split(' ').collect();
+    let searched_words: Vec<&str> = searched_name.split(' ').collect();
+    if !searched_words
+        .iter()
+        .all(|word| school_words.contains(word))
+    {
+        return None;
+    }
+    let extra_words = school_words
+        .iter()
+        .filter(|word| !searched_words.contains(word))
+        .count();
+    Some((1, extra_words))
+}
+
 impl<Username, Password> InactiveClient<Username, Password> {
     pub const fn new(
         auth_data: AuthData<Username, Password>,
@@ -93,20 +207,132 @@ impl<Username: Borrow<str> + Send + Sync, Password: Borrow<str> + Send + Sync>
         Ok(schools)
     }
 
+    /// Lists the options of the address hierarchy `level` that are allowed by the choices in the
+    /// `selection`. Going from `AddressLevel::Country` to `AddressLevel::School`, choosing the
+    /// options with `AddressSelection::select` and finishing with
+    /// `AddressSelection::school_info` gives a `SchoolInfo`.
+    pub async fn address_options(
+        &self,
+        selection: &AddressSelection,
+        level: AddressLevel,
+    ) -> Result<Vec<NamedEntity>, SchoolsGettingError> {
+        match self
+            .web_client
+            .get("loginform")
+            .unwrap()
+            .query(&AddressOptionsRequest {
+                selection,
+                level: level.key(),
+            })
+            .send()
+            .await
+        {
+            Ok(resp) => match resp.json::<AddressOptionsResponse>().await {
+                Ok(response) => Ok(response.items),
+                Err(_error) => Err(SchoolsGettingError::InvalidJsonInServerResponse),
+            },
+            Err(error) => Err(SchoolsGettingError::RequestError(error)),
+        }
+    }
+
+    /// Searches the schools by a part of their name on the server side.
+    pub async fn search_schools(
+        &self,
+        name: &str,
+        skip: u32,
+        take: u32,
+    ) -> Result<Vec<SchoolInfo>, SchoolsGettingError> {
+        match self
+            .web_client
+            .get("schools/search")
+            .unwrap()
+            .query(&SchoolSearchRequest {
+                name,
+                with_address: true,
+                skip,
+                take,
+            })
+            .send()
+            .await
+        {
+            Ok(resp) => match resp.json().await {
+                Ok(schools) => Ok(schools),
+                Err(_error) => Err(SchoolsGettingError::InvalidJsonInServerResponse),
+            },
+            Err(error) => Err(SchoolsGettingError::RequestError(error)),
+        }
+    }
+
+    /// Searches the schools page by page, up to `SCHOOLS_BY_NAME_MAX_PAGES` pages. Also tells
+    /// whether all the found schools were received.
+    async fn search_schools_by_pages(
+        &self,
+        name: &str,
+    ) -> Result<(Vec<SchoolInfo>, bool), SchoolsGettingError> {
+        let mut schools = Vec::new();
+        for page in 0..SCHOOLS_BY_NAME_MAX_PAGES {
+            let page_schools = self
+                .search_schools(
+                    name,
+                    page * SCHOOLS_BY_NAME_PAGE_SIZE,
+                    SCHOOLS_BY_NAME_PAGE_SIZE,
+                )
+                .await?;
+            let is_last_page = page_schools.len() < SCHOOLS_BY_NAME_PAGE_SIZE as usize;
+            schools.extend(page_schools);
+            if is_last_page {
+                return Ok((schools, true));
+            }
+        }
+        Ok((schools, false))
+    }
+
+    /// Returns the schools whose names have every word of the specified one, the ones with the
+    /// fewest extra words first. An exact match (ignoring case, spacing and quotes) always comes
+    /// first. The server is asked for the schools having the most specific word of the name, so
+    /// the names written with other quotes or in another word order are found too. If the word
+    /// finds more schools than can be looked through, the next word is searched as well.
     pub async fn school_by_name(
         &self,
         school_name: impl Borrow<str> + Send,
-    ) -> Result<SchoolInfo, SchoolByNameGettingError> {
-        #[allow(clippy::never_loop)]
-        let school_info = 'school: loop {
-            for school in self.schools().await? {
-                if school.name == school_name.borrow() {
-                    break 'school school;
+    ) -> Result<Vec<SchoolInfo>, SchoolByNameGettingError> {
+        let searched_name = normalize_school_name(school_name.borrow());
+        let query_groups = school_search_queries(school_name.borrow());
+        if query_groups.is_empty() {
+            return Err(SchoolByNameGettingError::SchoolNotFound);
+        }
+        let mut candidates: Vec<((u8, usize), SchoolInfo)> = Vec::new();
+        for queries in query_groups {
+            let mut all_found = true;
+            for query in queries {
+                let (schools, all_received) = self.search_schools_by_pages(&query).await?;
+                all_found &= all_received;
+                for school in schools {
+                    if candidates
+                        .iter()
+                        .any(|(_rank, candidate)| candidate.id == school.id)
+                    {
+                        continue;
+                    }
+                    if let Some(rank) =
+                        school_name_rank(&searched_name, &normalize_school_name(&school.name))
+                    {
+                        candidates.push((rank, school));
+                    }
                 }
             }
+            if all_found {
+                break;
+            }
+        }
+        if candidates.is_empty() {
             return Err(SchoolByNameGettingError::SchoolNotFound);
-        };
-        Ok(school_info)
+        }
+        candidates.sort_by_key(|(rank, _school)| *rank);
+        Ok(candidates
+            .into_iter()
+            .map(|(_rank, school)| school)
+            .collect())
     }
 
     #[allow(clippy::too_many_lines)]
@@ -213,3 +439,62 @@ impl<Username: Borrow<str> + Send + Sync, Password: Borrow<str> + Send + Sync>
         Ok(LoggedInClient::new(user_data, logged_in_web_client))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_school_name, school_name_rank, school_search_queries};
+
+    fn rank(searched_name: &str, school_name: &str) -> Option<(u8, usize)> {
+        school_name_rank(
+            &normalize_school_name(searched_name),
+            &normalize_school_name(school_name),
+        )
+    }
+
+    #[test]
+    fn specific_words_are_searched_first() {
+        assert_eq!(
+            school_search_queries("МБОУ «Средняя общеобразовательная школа №5» Заречного"),
+            [
+                vec!["Заречного"],
+                vec!["№5", "№ 5"],
+                vec!["общеобразовательная"],
+                vec!["Средняя"],
+                vec!["школа"],
+                vec!["МБОУ"],
+            ]
+        );
+        assert_eq!(
+            school_search_queries("Лицей 2 лицей"),
+            [vec!["Лицей"], vec!["№2", "№ 2"]]
+        );
+        assert_eq!(
+            school_search_queries("СОШ №12"),
+            [vec!["№12", "№ 12"], vec!["СОШ"]]
+        );
+        assert!(school_search_queries(" \t").is_empty());
+    }
+
+    #[test]
+    fn near_matches_are_ranked() {
+        assert_eq!(rank("МБОУ \"Школа № 5\"", "мбоу «школа №5»"), Some((0, 0)));
+        assert_eq!(rank("Школа 5", "МБОУ Школа 5"), Some((1, 1)));
+        assert_eq!(rank("Школа 5", "МБОУ Школа 5 Заречного"), Some((1, 2)));
+        assert_eq!(rank("5 школа МБОУ", "МБОУ Школа 5"), Some((1, 0)));
+        assert_eq!(rank("Лицей 2", "Гимназия 7"), None);
+        assert_eq!(rank("Лицей 2", "Лицей 7"), None);
+    }
+
+    #[test]
+    fn numbers_are_matched_as_whole_words() {
+        assert_eq!(rank("Школа №5", "Школа №5"), Some((0, 0)));
+        assert_eq!(rank("Школа №5", "Школа №50"), None);
+        assert_eq!(rank("Школа №50", "Школа №5"), None);
+    }
+
+    #[test]
+    fn empty_names_match_nothing() {
+        assert_eq!(rank("Школа 5", ""), None);
+        assert_eq!(rank("", "Школа 5"), None);
+    }
+}
@@ -1,25 +1,793 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::NaiveDate;
+use num::BigInt;
+use serde::de::DeserializeOwned;
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::{RwLock, RwLockReadGuard},
+};
+
 use crate::{
-    user_data::UserData,
-    web_clients::{logged_in_web_client::LoggedInWebClient, web_client_wrapper::WebClientWrapper},
+    attendance::{AttendanceRecord, AttendanceSummary},
+    reports::{
+        engine::{ReportEngine, ReportError},
+        parent_info_letter::{self, ParentInfoLetter},
+        student_attendance_grades::{self, StudentAttendanceGradesReport},
+        student_total::{self, StudentTotalReport},
+    },
+    schemas::{
+        AddressBook, Announcement, AnnouncementsRequest, Assignment, AssignmentAttachments,
+        AssignmentDetails, AttachmentsRequest, BellSchedule, CreatedEntity, Diary, DiaryRequest,
+        ForumMessage, ForumMessageRequest, ForumPage, ForumSection, ForumTopic, ForumTopicRequest,
+        MailBox, MailListRequest, MailOrder, MailPage, Message, NamedEntity, NewAttachment,
+        NewMessage, PageRequest, RecipientGroup, RecipientsRequest, ReportFilter, ReportRequest,
+        SchoolCard, SchoolInfo, SchoolYear, SendMessageRequest, Student, StudentIdRequest,
+        StudentYearRequest, SubjectFinalMarks, Term, Timetable, TimetableDay, UnreadCounters,
+        YearIdRequest,
+    },
+    user_data::{self, UserData, YearChange},
+    web_clients::{
+        logged_in_web_client::LoggedInWebClient, request_builder::RequestBuilder,
+        web_client_wrapper::WebClientWrapper,
+    },
 };
 
 use super::logged_out_client::LoggedOutClient;
 
+/// How often the requests check whether the school year has changed by default.
+pub const DEFAULT_YEAR_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 /// A client that is logged on.
 pub struct LoggedInClient<Username, Password> {
-    user_data: UserData<Username, Password>,
+    /// Behind a lock, because the requests refresh it when the school year changes.
+    user_data: RwLock<UserData<Username, Password>>,
     web_client: WebClientWrapper<LoggedInWebClient>,
+    year_check: Mutex<YearCheck>,
+}
+
+struct YearCheck {
+    /// `None` disables the checks.
+    interval: Option<Duration>,
+    last_check: Instant,
+    /// The change found by a check made by a request, until the caller takes it.
+    untaken_change: Option<YearChange>,
+}
+
+impl YearCheck {
+    fn is_due(&self) -> bool {
+        self.interval
+            .is_some_and(|interval| self.last_check.elapsed() >= interval)
+    }
+
+    fn record(&mut self, year_change: Option<YearChange>) {
+        self.last_check = Instant::now();
+        if let Some(year_change) = year_change {
+            // The changes that were not taken in between are merged into one.
+            self.untaken_change = Some(match self.untaken_change.take() {
+                Some(untaken_change) => YearChange {
+                    previous_year_id: untaken_change.previous_year_id,
+                    current_year_id: year_change.current_year_id,
+                },
+                None => year_change,
+            });
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DataGettingError {
+    RequestError(reqwest::Error),
+    /// It's either the response is not JSON at all, or it does not match the expected structure.
+    InvalidJsonInServerResponse,
+}
+
+#[derive(Debug)]
+pub enum AttachmentDownloadingError {
+    RequestError(reqwest::Error),
+    WritingError(std::io::Error),
+}
+
+impl From<user_data::MakingError> for DataGettingError {
+    fn from(error: user_data::MakingError) -> Self {
+        use crate::user_data::MakingError;
+
+        match error {
+            MakingError::RequestError(error) => Self::RequestError(error),
+            MakingError::InvalidJsonInServerResponse => Self::InvalidJsonInServerResponse,
+        }
+    }
+}
+
+impl From<DataGettingError> for ReportError {
+    fn from(error: DataGettingError) -> Self {
+        match error {
+            DataGettingError::RequestError(error) => Self::RequestError(error),
+            DataGettingError::InvalidJsonInServerResponse => Self::InvalidJsonInServerResponse,
+        }
+    }
+}
+
+async fn receive_json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, DataGettingError> {
+    Ok(user_data::receive_json(request).await?)
 }
 
 impl<Username, Password> LoggedInClient<Username, Password> {
-    pub const fn new(
+    pub fn new(
         user_data: UserData<Username, Password>,
         web_client: WebClientWrapper<LoggedInWebClient>,
     ) -> Self {
         Self {
-            user_data,
+            user_data: RwLock::new(user_data),
             web_client,
+            year_check: Mutex::new(YearCheck {
+                interval: Some(DEFAULT_YEAR_CHECK_INTERVAL),
+                last_check: Instant::now(),
+                untaken_change: None,
+            }),
+        }
+    }
+
+    /// Sets how much time has to pass since the last check before a request checks whether the
+    /// school year has changed. `None` disables these checks.
+    pub fn set_year_check_interval(&mut self, interval: Option<Duration>) {
+        self.year_check.get_mut().unwrap().interval = interval;
+    }
+
+    /// Takes the school year change found by a request since the last call, if there was one. All
+    /// the requests made after the change use the new year.
+    pub fn take_year_change(&self) -> Option<YearChange> {
+        self.year_check.lock().unwrap().untaken_change.take()
+    }
+
+    /// Makes all the following requests use the data of the student with the specified id.
+    /// Returns `false` (changing nothing) if the user has no access to such a student.
+    pub fn switch_student(&mut self, student_id: &BigInt) -> bool {
+        self.user_data.get_mut().switch_student(student_id)
+    }
+
+    pub const fn report_engine(&self) -> ReportEngine<'_> {
+        ReportEngine::new(&self.web_client)
+    }
+}
+
+impl<Username: Send + Sync, Password: Send + Sync> LoggedInClient<Username, Password> {
+    fn is_year_check_due(&self) -> bool {
+        self.year_check.lock().unwrap().is_due()
+    }
+
+    /// Gives the user data to a request, refreshing it first if the school year check is due.
+    async fn checked_user_data(
+        &self,
+    ) -> Result<RwLockReadGuard<'_, UserData<Username, Password>>, user_data::MakingError> {
+        if !self.is_year_check_due() {
+            return Ok(self.user_data.read().await);
+        }
+        let mut user_data = self.user_data.write().await;
+        // Another request may have made the check while this one was waiting for the lock.
+        if self.is_year_check_due() {
+            let year_change = user_data.refresh_year(&self.web_client).await?;
+            self.year_check.lock().unwrap().record(year_change);
+        }
+        Ok(user_data.downgrade())
+    }
+
+    pub async fn user_data(&self) -> RwLockReadGuard<'_, UserData<Username, Password>> {
+        self.user_data.read().await
+    }
+
+    /// Lists the students available to the user: one for a student, all the children for a
+    /// parent.
+    pub async fn students(&self) -> Vec<Student> {
+        self.user_data.read().await.students().to_vec()
+    }
+
+    /// Checks whether the school year has changed since the log-in (or the last check) and
+    /// refreshes the user data if it has, no matter when the last check was made. The returned
+    /// `YearChange` tells that every request made from now on will use the new year. The
+    /// requests make such checks by themselves, once in the year check interval.
+    pub async fn refresh_year(&self) -> Result<Option<YearChange>, DataGettingError> {
+        let year_change = self
+            .user_data
+            .write()
+            .await
+            .refresh_year(&self.web_client)
+            .await?;
+        self.year_check.lock().unwrap().last_check = Instant::now();
+        Ok(year_change)
+    }
+
+    pub async fn student_total_report(&self) -> Result<StudentTotalReport, ReportError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.student_total_report_in_year(&year_id).await
+    }
+
+    /// Same as `student_total_report`, but for the specified school year instead of the current
+    /// one.
+    pub async fn student_total_report_in_year(
+        &self,
+        year_id: &BigInt,
+    ) -> Result<StudentTotalReport, ReportError> {
+        let report = self
+            .report_engine()
+            .generate(
+                student_total::REPORT_NAME,
+                &student_total::request(self.checked_user_data().await?.student_id(), year_id),
+            )
+            .await?;
+        StudentTotalReport::parse(&report).ok_or(ReportError::UnexpectedReportStructure)
+    }
+
+    /// Gets the parent information letter for the specified term of the current school year.
+    pub async fn parent_info_letter(
+        &self,
+        term_id: &BigInt,
+    ) -> Result<ParentInfoLetter, ReportError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.parent_info_letter_in_year(&year_id, term_id).await
+    }
+
+    /// Same as `parent_info_letter`, but for a term of the specified school year.
+    pub async fn parent_info_letter_in_year(
+        &self,
+        year_id: &BigInt,
+        term_id: &BigInt,
+    ) -> Result<ParentInfoLetter, ReportError> {
+        let report = self
+            .report_engine()
+            .generate(
+                parent_info_letter::REPORT_NAME,
+                &parent_info_letter::request(
+                    self.checked_user_data().await?.student_id(),
+                    year_id,
+                    term_id,
+                ),
+            )
+            .await?;
+        ParentInfoLetter::parse(report).ok_or(ReportError::UnexpectedReportStructure)
+    }
+
+    /// Lists all the school years available on the server, including the past ones.
+    pub async fn years(&self) -> Result<Vec<SchoolYear>, DataGettingError> {
+        receive_json(self.web_client.get("mysettings/yearlist").unwrap()).await
+    }
+
+    /// Lists the terms of the current school year.
+    pub async fn terms(&self) -> Result<Vec<Term>, DataGettingError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.terms_in_year(&year_id).await
+    }
+
+    /// Same as `terms`, but for the specified school year instead of the current one.
+    pub async fn terms_in_year(&self, year_id: &BigInt) -> Result<Vec<Term>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get("grade/terms")
+                .unwrap()
+                .query(&YearIdRequest { year_id }),
+        )
+        .await
+    }
+
+    /// Gets the term, year, exam and final marks of the current student for every subject.
+    pub async fn final_marks(&self) -> Result<Vec<SubjectFinalMarks>, DataGettingError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.final_marks_in_year(&year_id).await
+    }
+
+    /// Same as `final_marks`, but for the specified school year instead of the current one.
+    pub async fn final_marks_in_year(
+        &self,
+        year_id: &BigInt,
+    ) -> Result<Vec<SubjectFinalMarks>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get("grade/totalmarks")
+                .unwrap()
+                .query(&StudentYearRequest {
+                    student_id: self.checked_user_data().await?.student_id(),
+                    year_id,
+                }),
+        )
+        .await
+    }
+
+    /// Gets the diary of the current student for the specified week (both ends are included).
+    pub async fn diary(
+        &self,
+        week_start: NaiveDate,
+        week_end: NaiveDate,
+    ) -> Result<Diary, DataGettingError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.diary_in_year(&year_id, week_start, week_end).await
+    }
+
+    /// Same as `diary`, but for the specified school year instead of the current one.
+    pub async fn diary_in_year(
+        &self,
+        year_id: &BigInt,
+        week_start: NaiveDate,
+        week_end: NaiveDate,
+    ) -> Result<Diary, DataGettingError> {
+        let user_data = self.checked_user_data().await?;
+        let mut diary: Diary = receive_json(self.web_client.get("student/diary").unwrap().query(
+            &DiaryRequest {
+                student_id: user_data.student_id(),
+                year_id,
+                week_start,
+                week_end,
+            },
+        ))
+        .await?;
+        for day in &mut diary.days {
+            for lesson in &mut day.lessons {
+                for assignment in &mut lesson.assignments {
+                    assignment.resolve_type_name(user_data.assignment_types());
+                }
+            }
+        }
+        Ok(diary)
+    }
+
+    /// Gets the timetable of the current student's class for the specified week (both ends are
+    /// included).
+    pub async fn timetable(
+        &self,
+        week_start: NaiveDate,
+        week_end: NaiveDate,
+    ) -> Result<Timetable, DataGettingError> {
+        let user_data = self.checked_user_data().await?;
+        receive_json(
+            self.web_client
+                .get("schedule/week")
+                .unwrap()
+                .query(&DiaryRequest {
+                    student_id: user_data.student_id(),
+                    year_id: user_data.year_id(),
+                    week_start,
+                    week_end,
+                }),
+        )
+        .await
+    }
+
+    /// Gets the timetable for a single day. A day without lessons has an empty lesson list.
+    pub async fn timetable_for_day(
+        &self,
+        date: NaiveDate,
+    ) -> Result<TimetableDay, DataGettingError> {
+        let timetable = self.timetable(date, date).await?;
+        Ok(timetable
+            .days
+            .into_iter()
+            .find(|day| day.date == date)
+            .unwrap_or_else(|| TimetableDay {
+                date,
+                lessons: Vec::new(),
+            }))
+    }
+
+    /// Gets the absence marks of the current student for the specified period (both ends are
+    /// included). They are taken from the attendance report, because the diary does not list the
+    /// lessons without assignments.
+    pub async fn attendance(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<AttendanceRecord>, ReportError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.attendance_in_year(&year_id, start, end).await
+    }
+
+    /// Same as `attendance`, but for a period of the specified school year.
+    pub async fn attendance_in_year(
+        &self,
+        year_id: &BigInt,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<AttendanceRecord>, ReportError> {
+        let report = self
+            .report_engine()
+            .generate(
+                student_attendance_grades::REPORT_NAME,
+                &student_attendance_grades::request(
+                    self.checked_user_data().await?.student_id(),
+                    year_id,
+                    start,
+                    end,
+                ),
+            )
+            .await?;
+        StudentAttendanceGradesReport::parse(&report, start, end)
+            .map(|report| report.absences)
+            .ok_or(ReportError::UnexpectedReportStructure)
+    }
+
+    /// Counts the lessons missed in the specified period of the current school year, for every
+    /// subject and every term.
+    pub async fn attendance_summary(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<AttendanceSummary, ReportError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.attendance_summary_in_year(&year_id, start, end).await
+    }
+
+    /// Same as `attendance_summary`, but for a period of the specified school year, so that the
+    /// absences are counted by the terms of that year.
+    pub async fn attendance_summary_in_year(
+        &self,
+        year_id: &BigInt,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<AttendanceSummary, ReportError> {
+        let records = self.attendance_in_year(year_id, start, end).await?;
+        Ok(AttendanceSummary::new(
+            &records,
+            &self.terms_in_year(year_id).await?,
+        ))
+    }
+
+    /// Gets the assignments that were due in the specified period, but were not done.
+    pub async fn overdue(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Assignment>, DataGettingError> {
+        let year_id = self.checked_user_data().await?.year_id().clone();
+        self.overdue_in_year(&year_id, start, end).await
+    }
+
+    /// Same as `overdue`, but for the specified school year instead of the current one.
+    pub async fn overdue_in_year(
+        &self,
+        year_id: &BigInt,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<Assignment>, DataGettingError> {
+        let user_data = self.checked_user_data().await?;
+        let mut assignments: Vec<Assignment> = receive_json(
+            self.web_client
+                .get("student/diary/pastMandatory")
+                .unwrap()
+                .query(&DiaryRequest {
+                    student_id: user_data.student_id(),
+                    year_id,
+                    week_start: start,
+                    week_end: end,
+                }),
+        )
+        .await?;
+        for assignment in &mut assignments {
+            assignment.resolve_type_name(user_data.assignment_types());
+        }
+        Ok(assignments)
+    }
+
+    pub async fn assignment(&self, id: &BigInt) -> Result<AssignmentDetails, DataGettingError> {
+        let user_data = self.checked_user_data().await?;
+        let mut assignment: AssignmentDetails = receive_json(
+            self.web_client
+                .get(&format!("student/diary/assigns/{}", id))
+                .unwrap()
+                .query(&StudentIdRequest {
+                    student_id: user_data.student_id(),
+                }),
+        )
+        .await?;
+        assignment.resolve_type_name(user_data.assignment_types());
+        Ok(assignment)
+    }
+
+    /// Lists the attachments of every specified assignment. Assignments without attachments may
+    /// be omitted by the server.
+    pub async fn attachments(
+        &self,
+        assignment_ids: &[BigInt],
+    ) -> Result<Vec<AssignmentAttachments>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .post("student/diary/get-attachments")
+                .unwrap()
+                .query(&StudentIdRequest {
+                    student_id: self.checked_user_data().await?.student_id(),
+                })
+                .json(&AttachmentsRequest { assignment_ids }),
+        )
+        .await
+    }
+
+    /// Writes the contents of the attachment to the `destination` chunk by chunk, as they are
+    /// received.
+    pub async fn download_attachment(
+        &self,
+        attachment_id: &BigInt,
+        destination: &mut (impl AsyncWrite + Unpin + Send),
+    ) -> Result<(), AttachmentDownloadingError> {
+        let mut response = match self
+            .web_client
+            .get(&format!("attachments/{}", attachment_id))
+            .unwrap()
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(error) => return Err(AttachmentDownloadingError::RequestError(error)),
+        };
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Err(error) = destination.write_all(&chunk).await {
+                        return Err(AttachmentDownloadingError::WritingError(error));
+                    }
+                }
+                Ok(None) => break,
+                Err(error) => return Err(AttachmentDownloadingError::RequestError(error)),
+            }
+        }
+        destination
+            .flush()
+            .await
+            .map_err(AttachmentDownloadingError::WritingError)
+    }
+
+    /// Gets the latest `take` announcements (newest first) or all of them if `take` is `None`.
+    pub async fn announcements(
+        &self,
+        take: Option<u32>,
+    ) -> Result<Vec<Announcement>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get("announcements")
+                .unwrap()
+                .query(&AnnouncementsRequest {
+                    take: take.map_or(-1, i64::from),
+                }),
+        )
+        .await
+    }
+
+    pub async fn school_card(&self, school: &SchoolInfo) -> Result<SchoolCard, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get(&format!("schools/{}/card", school.id))
+                .unwrap(),
+        )
+        .await
+    }
+
+    /// Gets the card of the school the user is logged in to.
+    pub async fn own_school_card(&self) -> Result<SchoolCard, DataGettingError> {
+        self.school_card(&self.user_data.read().await.auth_data().school_info)
+            .await
+    }
+
+    pub async fn bell_schedule(
+        &self,
+        school: &SchoolInfo,
+    ) -> Result<BellSchedule, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get(&format!("schools/{}/bells", school.id))
+                .unwrap(),
+        )
+        .await
+    }
+
+    /// Gets the bell schedule of the school the user is logged in to.
+    pub async fn own_bell_schedule(&self) -> Result<BellSchedule, DataGettingError> {
+        self.bell_schedule(&self.user_data.read().await.auth_data().school_info)
+            .await
+    }
+
+    /// Gets a page of the mail box, the newest messages first. Pages start from 1.
+    pub async fn mail_box(
+        &self,
+        mail_box: MailBox,
+        page: u32,
+        page_size: u32,
+    ) -> Result<MailPage, DataGettingError> {
+        receive_json(
+            self.web_client
+                .post("mail/registry")
+                .unwrap()
+                .json(&MailListRequest {
+                    filter_context: ReportRequest {
+                        filters: vec![ReportFilter {
+                            id: "MailBox".to_owned(),
+                            value: mail_box.filter_value().to_owned(),
+                            text: String::new(),
+                        }],
+                        params: Vec::new(),
+                    },
+                    fields: &["author", "subject", "sent", "read"],
+                    page,
+                    page_size,
+                    order: MailOrder {
+                        field_id: "sent",
+                        ascending: false,
+                    },
+                }),
+        )
+        .await
+    }
+
+    /// Gets the full message. Marks it as read.
+    pub async fn message(&self, id: &BigInt) -> Result<Message, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get(&format!("mail/messages/{}/read", id))
+                .unwrap(),
+        )
+        .await
+    }
+
+    /// Uploads the attachments and sends the message. Returns the id of the sent message.
+    pub async fn send_message(&self, message: NewMessage) -> Result<BigInt, DataGettingError> {
+        let mut file_ids = message.attached_file_ids;
+        for attachment in message.attachments {
+            let uploaded_file: CreatedEntity = receive_json(
+                self.web_client.post("mail/files").unwrap().multipart(
+                    reqwest::multipart::Form::new().part(
+                        "file",
+                        reqwest::multipart::Part::bytes(attachment.contents)
+                            .file_name(attachment.file_name),
+                    ),
+                ),
+            )
+            .await?;
+            file_ids.push(uploaded_file.id);
         }
+        let sent_message: CreatedEntity =
+            receive_json(self.web_client.post("mail/messages").unwrap().json(
+                &SendMessageRequest {
+                    to: &message.recipients,
+                    copy_to: &message.copy_recipients,
+                    subject: &message.subject,
+                    text: &message.text,
+                    file_ids: &file_ids,
+                },
+            ))
+            .await?;
+        Ok(sent_message.id)
+    }
+
+    /// Replies to the author of the `original` message. Returns the id of the sent message.
+    pub async fn reply(
+        &self,
+        original: &Message,
+        text: String,
+        attachments: Vec<NewAttachment>,
+    ) -> Result<BigInt, DataGettingError> {
+        self.send_message(NewMessage {
+            attachments,
+            ..NewMessage::reply(original, text)
+        })
+        .await
+    }
+
+    /// Forwards the `original` message with its attachments. Returns the id of the sent message.
+    pub async fn forward(
+        &self,
+        original: &Message,
+        recipients: Vec<BigInt>,
+        text: String,
+    ) -> Result<BigInt, DataGettingError> {
+        self.send_message(NewMessage::forward(original, recipients, text))
+            .await
+    }
+
+    /// Lists the users of the `group` the mail can be sent to.
+    pub async fn recipients(
+        &self,
+        group: RecipientGroup,
+    ) -> Result<Vec<NamedEntity>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get("mail/recipients")
+                .unwrap()
+                .query(&RecipientsRequest { group: group.key() }),
+        )
+        .await
+    }
+
+    /// Lists the recipients of all the groups.
+    pub async fn address_book(&self) -> Result<AddressBook, DataGettingError> {
+        let mut groups = Vec::new();
+        for group in RecipientGroup::ALL {
+            match self.recipients(group).await {
+                Ok(recipients) => groups.push((group, recipients)),
+                Err(DataGettingError::RequestError(error))
+                    if error.status() == Some(reqwest::StatusCode::FORBIDDEN) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(AddressBook { groups })
+    }
+
+    /// Gets the numbers of the unread messages and the new announcements. Only asks for the
+    /// numbers, so it is cheap enough to be polled often.
+    pub async fn unread_counters(&self) -> Result<UnreadCounters, DataGettingError> {
+        Ok(UnreadCounters {
+            // "unreaded" is how the server spells it.
+            mail: receive_json(self.web_client.get("mail/messages/unreaded").unwrap()).await?,
+            announcements: receive_json(self.web_client.get("announcements/unreadcount").unwrap())
+                .await?,
+        })
+    }
+
+    pub async fn forum_sections(&self) -> Result<Vec<ForumSection>, DataGettingError> {
+        receive_json(self.web_client.get("forum/sections").unwrap()).await
+    }
+
+    /// Gets a page of the topics of the section, skipping the first `skip` ones.
+    pub async fn forum_topics(
+        &self,
+        section_id: &BigInt,
+        skip: u32,
+        take: u32,
+    ) -> Result<ForumPage<ForumTopic>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get(&format!("forum/sections/{}/topics", section_id))
+                .unwrap()
+                .query(&PageRequest { skip, take }),
+        )
+        .await
+    }
+
+    /// Gets a page of the messages of the topic, skipping the first `skip` ones.
+    pub async fn forum_messages(
+        &self,
+        topic_id: &BigInt,
+        skip: u32,
+        take: u32,
+    ) -> Result<ForumPage<ForumMessage>, DataGettingError> {
+        receive_json(
+            self.web_client
+                .get(&format!("forum/topics/{}/messages", topic_id))
+                .unwrap()
+                .query(&PageRequest { skip, take }),
+        )
+        .await
+    }
+
+    /// Posts a message to the topic. Returns the id of the posted message.
+    pub async fn post_forum_message(
+        &self,
+        topic_id: &BigInt,
+        text: &str,
+    ) -> Result<BigInt, DataGettingError> {
+        let message: CreatedEntity = receive_json(
+            self.web_client
+                .post(&format!("forum/topics/{}/messages", topic_id))
+                .unwrap()
+                .json(&ForumMessageRequest { text }),
+        )
+        .await?;
+        Ok(message.id)
+    }
+
+    /// Starts a topic in the section with the `text` as its first message. Returns the id of the
+    /// created topic.
+    pub async fn create_forum_topic(
+        &self,
+        section_id: &BigInt,
+        name: &str,
+        text: &str,
+    ) -> Result<BigInt, DataGettingError> {
+        let topic: CreatedEntity = receive_json(
+            self.web_client
+                .post(&format!("forum/sections/{}/topics", section_id))
+                .unwrap()
+                .json(&ForumTopicRequest { name, text }),
+        )
+        .await?;
+        Ok(topic.id)
     }
 }
 
@@ -33,7 +801,7 @@ impl<Username: Send, Password: Send> LoggedInClient<Username, Password> {
         let logging_out_result = self.web_client.post("auth/logout").unwrap().send().await;
         (
             LoggedOutClient::new(
-                self.user_data,
+                self.user_data.into_inner(),
                 WebClientWrapper::new(self.web_client.into_inner().log_out()),
             ),
             logging_out_result,
@@ -45,14 +813,14 @@ impl<Username: Send, Password: Send> LoggedInClient<Username, Password> {
     ) -> Result<LoggedOutClient<Username, Password>, (reqwest::Error, Self)> {
         match self.web_client.post("auth/logout").unwrap().send().await {
             Ok(_resp) => Ok(LoggedOutClient::new(
-                self.user_data,
+                self.user_data.into_inner(),
                 WebClientWrapper::new(self.web_client.into_inner().log_out()),
             )),
             Err(error) => {
                 if let Some(status) = error.status() {
                     if status == reqwest::StatusCode::UNAUTHORIZED {
                         return Ok(LoggedOutClient::new(
-                            self.user_data,
+                            self.user_data.into_inner(),
                             WebClientWrapper::new(self.web_client.into_inner().log_out()),
                         ));
                     }
@@ -62,3 +830,48 @@ impl<Username: Send, Password: Send> LoggedInClient<Username, Password> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use num::BigInt;
+
+    use crate::user_data::YearChange;
+
+    use super::YearCheck;
+
+    fn change(previous_year_id: u8, current_year_id: u8) -> YearChange {
+        YearChange {
+            previous_year_id: previous_year_id.into(),
+            current_year_id: current_year_id.into(),
+        }
+    }
+
+    #[test]
+    fn untaken_changes_are_merged() {
+        let mut year_check = YearCheck {
+            interval: Some(Duration::from_secs(60)),
+            last_check: Instant::now(),
+            untaken_change: None,
+        };
+        assert!(!year_check.is_due());
+        year_check.record(None);
+        assert!(year_check.untaken_change.is_none());
+        year_check.record(Some(change(1, 2)));
+        year_check.record(Some(change(2, 3)));
+        let untaken_change = year_check.untaken_change.take().unwrap();
+        assert_eq!(untaken_change.previous_year_id, BigInt::from(1));
+        assert_eq!(untaken_change.current_year_id, BigInt::from(3));
+    }
+
+    #[test]
+    fn disabled_check_is_never_due() {
+        let year_check = YearCheck {
+            interval: None,
+            last_check: Instant::now() - Duration::from_secs(60),
+            untaken_change: None,
+        };
+        assert!(!year_check.is_due());
+    }
+}
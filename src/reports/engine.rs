@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use num::BigInt;
+
+use crate::{
+    schemas::{ReportQueueResponse, ReportRequest, ReportTask, ReportTaskStatus},
+    user_data,
+    web_clients::{logged_in_web_client::LoggedInWebClient, web_client_wrapper::WebClientWrapper},
+};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum ReportError {
+    RequestError(reqwest::Error),
+    /// It's either the response is not JSON at all, or it does not match the expected structure.
+    InvalidJsonInServerResponse,
+    /// The server could not generate the report. Contains the reason if the server told it.
+    GenerationFailed(Option<String>),
+    /// The report was not generated in time. Contains the id of the task, so the waiting can be
+    /// continued.
+    TimedOut(BigInt),
+    /// The report was generated, but it does not look like the report that was requested.
+    UnexpectedReportStructure,
+}
+
+impl From<user_data::MakingError> for ReportError {
+    fn from(error: user_data::MakingError) -> Self {
+        use crate::user_data::MakingError;
+
+        match error {
+            MakingError::RequestError(error) => Self::RequestError(error),
+            MakingError::InvalidJsonInServerResponse => Self::InvalidJsonInServerResponse,
+        }
+    }
+}
+
+/// Reports are not generated on request: the generation is queued as a task, which has to be
+/// waited for, and only then the result can be downloaded. This engine does all of that.
+pub struct ReportEngine<'web_client> {
+    web_client: &'web_client WebClientWrapper<LoggedInWebClient>,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl<'web_client> ReportEngine<'web_client> {
+    pub const fn new(web_client: &'web_client WebClientWrapper<LoggedInWebClient>) -> Self {
+        Self {
+            web_client,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Sets how often the task state is checked while waiting.
+    #[must_use]
+    pub const fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets how long to wait for the task to complete.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Queues the generation of the report with the specified name ("studenttotal", for
+    /// example). Returns the id of the queued task.
+    pub async fn start(
+        &self,
+        report: &str,
+        request: &ReportRequest,
+    ) -> Result<BigInt, ReportError> {
+        match self
+            .web_client
+            .post(&format!("reports/{}/queue", report))
+            .unwrap()
+            .json(request)
+            .send()
+            .await
+        {
+            Ok(resp) => match resp.json::<ReportQueueResponse>().await {
+                Ok(response) => Ok(response.task_id),
+                Err(_error) => Err(ReportError::InvalidJsonInServerResponse),
+            },
+            Err(error) => Err(ReportError::RequestError(error)),
+        }
+    }
+
+    /// Waits for the task to complete and downloads the generated report.
+    pub async fn wait(&self, task_id: BigInt) -> Result<String, ReportError> {
+        let deadline = Instant::now() + self.timeout;
+        let file_id = loop {
+            let task: ReportTask = match self
+                .web_client
+                .get(&format!("tasks/{}", task_id))
+                .unwrap()
+                .send()
+                .await
+            {
+                Ok(resp) => match resp.json().await {
+                    Ok(task) => task,
+                    Err(_error) => return Err(ReportError::InvalidJsonInServerResponse),
+                },
+                Err(error) => return Err(ReportError::RequestError(error)),
+            };
+            match task.status {
+                ReportTaskStatus::Completed => match task.file_id {
+                    Some(file_id) => break file_id,
+                    None => return Err(ReportError::InvalidJsonInServerResponse),
+                },
+                ReportTaskStatus::Failed => {
+                    return Err(ReportError::GenerationFailed(task.error_message))
+                }
+                ReportTaskStatus::Running => {
+                    if Instant::now() + self.poll_interval > deadline {
+                        return Err(ReportError::TimedOut(task_id));
+                    }
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+            }
+        };
+        match self
+            .web_client
+            .get(&format!("files/{}", file_id))
+            .unwrap()
+            .send()
+            .await
+        {
+            Ok(resp) => resp.text().await.map_err(ReportError::RequestError),
+            Err(error) => Err(ReportError::RequestError(error)),
+        }
+    }
+
+    /// Queues the report and waits for it.
+    pub async fn generate(
+        &self,
+        report: &str,
+        request: &ReportRequest,
+    ) -> Result<String, ReportError> {
+        let task_id = self.start(report, request).await?;
+        self.wait(task_id).await
+    }
+}
@@ -0,0 +1,164 @@
+use scraper::{ElementRef, Html, Selector};
+
+/// A table from a generated report, as the texts of its cells. A cell spanning several rows or
+/// columns is repeated in every position it covers, so the cells of a column always have the
+/// same index.
+pub type Table = Vec<Vec<String>>;
+
+type Rows = [Vec<String>];
+
+/// Extracts all the tables from the report. Nested tables are extracted separately and also
+/// stay in the text of the cells containing them.
+pub fn tables(html: &str) -> Vec<Table> {
+    let document = Html::parse_document(html);
+    let table_selector = Selector::parse("table").unwrap();
+    document.select(&table_selector).map(rows).collect()
+}
+
+fn rows(table: ElementRef) -> Table {
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("th, td").unwrap();
+    let mut rows: Table = Vec::new();
+    // For every column: how many more rows the cell above covers, and the text of that cell.
+    let mut spans: Vec<(usize, String)> = Vec::new();
+    for row in table
+        .select(&row_selector)
+        .filter(|row| is_own(*row, table, "table"))
+    {
+        let mut cells = Vec::new();
+        let mut row_cells = row
+            .select(&cell_selector)
+            .filter(|cell| is_own(*cell, row, "tr"));
+        loop {
+            let column = cells.len();
+            if let Some((remaining_rows @ 1.., text)) = spans.get_mut(column) {
+                *remaining_rows -= 1;
+                cells.push(text.clone());
+                continue;
+            }
+            let cell = match row_cells.next() {
+                Some(cell) => cell,
+                None => break,
+            };
+            let text = cell_text(cell);
+            let column_span = span(cell, "colspan");
+            let row_span = span(cell, "rowspan");
+            for _ in 0..column_span {
+                let column = cells.len();
+                if spans.len() <= column {
+                    spans.resize(column + 1, (0, String::new()));
+                }
+                spans[column] = (row_span - 1, text.clone());
+                cells.push(text.clone());
+            }
+        }
+        if !cells.is_empty() {
+            rows.push(cells);
+        }
+    }
+    rows
+}
+
+/// Tells whether the `element` belongs to the `container` itself and not to a nested element of
+/// the same kind (a table inside a cell of the table, for example).
+fn is_own(element: ElementRef, container: ElementRef, container_name: &str) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|ancestor| ancestor.value().name() == container_name)
+        .is_some_and(|ancestor| ancestor.id() == container.id())
+}
+
+fn span(cell: ElementRef, attribute: &str) -> usize {
+    cell.value()
+        .attr(attribute)
+        .and_then(|span| span.trim().parse().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// The text of the cell with all the whitespace sequences replaced with single spaces.
+fn cell_text(cell: ElementRef) -> String {
+    cell.text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses a number written the Russian way ("4,67").
+pub fn number(text: &str) -> Option<f64> {
+    text.trim().replace(',', ".").parse().ok()
+}
+
+/// `None` for the empty cells and the cells with just a dash.
+pub fn non_empty(text: &str) -> Option<String> {
+    if text.is_empty() || text == "-" {
+        None
+    } else {
+        Some(text.to_owned())
+    }
+}
+
+/// Splits the table into the header row and the body rows. The header is found by its first
+/// cell, which should start with `first_header` (ignoring case). If the header takes several
+/// rows, the last (the most specific) one is returned.
+pub fn split_header<'table>(
+    table: &'table Table,
+    first_header: &str,
+) -> Option<(&'table [String], &'table Rows)> {
+    let (header_rows, body) = split_header_rows(table, first_header)?;
+    Some((header_rows.last()?, body))
+}
+
+/// Same as `split_header`, but returns all the rows of the header, for the tables whose columns
+/// are only told apart by several rows (a month above the days of it, for example).
+pub fn split_header_rows<'table>(
+    table: &'table Table,
+    first_header: &str,
+) -> Option<(&'table Rows, &'table Rows)> {
+    let is_header = |row: &Vec<String>| {
+        row.first()
+            .is_some_and(|cell| cell.to_lowercase().starts_with(first_header))
+    };
+    let header_start = table.iter().position(is_header)?;
+    let header_end = header_start
+        + table[header_start..]
+            .iter()
+            .position(|row| !is_header(row))?;
+    Some((&table[header_start..header_end], &table[header_end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_header, tables};
+
+    #[test]
+    fn spanned_cells_are_repeated() {
+        let tables = tables(
+            "<table>\
+                <tr><th rowspan=\"2\">Предмет</th><th colspan=\"2\">1 полугодие</th></tr>\
+                <tr><th>Средний балл</th><th>Отметка</th></tr>\
+                <tr><td>Химия</td><td rowspan=\"2\">4,5</td><td>5</td></tr>\
+                <tr><td>Физика</td><td>  4 </td></tr>\
+            </table>",
+        );
+        assert_eq!(
+            tables,
+            [[
+                ["Предмет", "1 полугодие", "1 полугодие"],
+                ["Предмет", "Средний балл", "Отметка"],
+                ["Химия", "4,5", "5"],
+                ["Физика", "4,5", "4"],
+            ]]
+        );
+        let (header, body) = split_header(&tables[0], "предмет").unwrap();
+        assert_eq!(header, ["Предмет", "Средний балл", "Отметка"]);
+        assert_eq!(body.len(), 2);
+    }
+
+    #[test]
+    fn nested_tables_are_extracted_separately() {
+        let tables = tables("<table><tr><td>a<table><tr><td>b</td></tr></table></td></tr></table>");
+        assert_eq!(tables, [vec![vec!["a b"]], vec![vec!["b"]]]);
+    }
+}
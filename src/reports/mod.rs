@@ -0,0 +1,5 @@
+pub mod engine;
+pub mod html_table;
+pub mod parent_info_letter;
+pub mod student_attendance_grades;
+pub mod student_total;
@@ -0,0 +1,165 @@
+use num::BigInt;
+
+use crate::schemas::{ReportFilter, ReportParam, ReportRequest};
+
+use super::html_table::{self, Table};
+
+pub const REPORT_NAME: &str = "parentinfoletter";
+
+/// The "Информационное письмо для родителей" report: how many marks of every value the student
+/// got in every subject during the period, the average marks and the marks for the period.
+#[derive(Debug)]
+pub struct ParentInfoLetter {
+    pub subjects: Vec<SubjectMarksSummary>,
+    /// The report as it was rendered by the server, for showing or printing it as is.
+    pub html: String,
+}
+
+#[derive(Debug)]
+pub struct SubjectMarksSummary {
+    pub subject: String,
+    /// How many marks of every value were received, in the order of the report columns (from the
+    /// highest mark, usually).
+    pub mark_counts: Vec<(String, usize)>,
+    pub average: Option<f64>,
+    /// The mark for the whole period. A string, because besides the numbers it can be "зач",
+    /// "н/а" and so on.
+    pub period_mark: Option<String>,
+}
+
+enum Column {
+    Subject,
+    /// Contains the mark value.
+    MarkCount(String),
+    Average,
+    PeriodMark,
+    Other,
+}
+
+impl Column {
+    fn from_header(header: &str) -> Self {
+        let lowercase_header = header.to_lowercase();
+        if lowercase_header.starts_with("предмет") {
+            Self::Subject
+        } else if header.parse::<u32>().is_ok() {
+            Self::MarkCount(header.to_owned())
+        } else if lowercase_header.contains("средн") {
+            Self::Average
+        } else if lowercase_header.contains("оценка") || lowercase_header.contains("отметка")
+        {
+            Self::PeriodMark
+        } else {
+            Self::Other
+        }
+    }
+}
+
+pub fn request(student_id: &BigInt, year_id: &BigInt, term_id: &BigInt) -> ReportRequest {
+    ReportRequest {
+        filters: vec![
+            ReportFilter {
+                id: "SID".to_owned(),
+                value: student_id.to_string(),
+                text: String::new(),
+            },
+            ReportFilter {
+                id: "TERMID".to_owned(),
+                value: term_id.to_string(),
+                text: String::new(),
+            },
+        ],
+        params: vec![ReportParam {
+            name: "SCHOOLYEARID".to_owned(),
+            value: year_id.to_string(),
+        }],
+    }
+}
+
+impl ParentInfoLetter {
+    /// Parses the generated report. `None` if the report does not contain the table of marks.
+    pub fn parse(html: String) -> Option<Self> {
+        let subjects = html_table::tables(&html)
+            .iter()
+            .find_map(Self::subjects_from_table)?;
+        Some(Self { subjects, html })
+    }
+
+    fn subjects_from_table(table: &Table) -> Option<Vec<SubjectMarksSummary>> {
+        let (header, body) = html_table::split_header(table, "предмет")?;
+        let columns: Vec<Column> = header
+            .iter()
+            .map(|header| Column::from_header(header))
+            .collect();
+        if !columns
+            .iter()
+            .any(|column| matches!(column, Column::MarkCount(_)))
+        {
+            return None;
+        }
+        // A count that is not a number means the table is not the one expected, so the whole
+        // table is rejected instead of counting such a cell as zero.
+        body.iter()
+            .filter(|row| row.first().is_some_and(|subject| !subject.is_empty()))
+            .map(|row| {
+                let mut summary = SubjectMarksSummary {
+                    subject: String::new(),
+                    mark_counts: Vec::new(),
+                    average: None,
+                    period_mark: None,
+                };
+                for (cell, column) in row.iter().zip(&columns) {
+                    match column {
+                        Column::Subject => summary.subject = cell.clone(),
+                        Column::MarkCount(mark) => {
+                            let count = match html_table::non_empty(cell) {
+                                Some(count) => count.parse().ok()?,
+                                None => 0,
+                            };
+                            summary.mark_counts.push((mark.clone(), count));
+                        }
+                        Column::Average => summary.average = html_table::number(cell),
+                        Column::PeriodMark => {
+                            summary.period_mark = html_table::non_empty(cell);
+                        }
+                        Column::Other => {}
+                    }
+                }
+                Some(summary)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParentInfoLetter;
+
+    const HEADER: &str = "<tr><th>Предмет</th><th>5</th><th>4</th><th>Средний балл</th>\
+        <th>Отметка за период</th></tr>";
+
+    #[test]
+    fn empty_counts_are_zero() {
+        let letter = ParentInfoLetter::parse(format!(
+            "<table>{HEADER}<tr><td>Физика</td><td>3</td><td>-</td><td>4,75</td><td>5</td></tr>\
+            </table>"
+        ))
+        .unwrap();
+        let physics = &letter.subjects[0];
+        assert_eq!(physics.subject, "Физика");
+        assert_eq!(
+            physics.mark_counts,
+            [("5".to_owned(), 3), ("4".to_owned(), 0)]
+        );
+        assert_eq!(physics.average, Some(4.75));
+        assert_eq!(physics.period_mark.as_deref(), Some("5"));
+    }
+
+    #[test]
+    fn unparsable_counts_are_rejected() {
+        assert!(ParentInfoLetter::parse(format!(
+            "<table>{HEADER}<tr><td>Физика</td><td>три</td><td></td><td></td><td></td></tr>\
+            </table>"
+        ))
+        .is_none());
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{Datelike, NaiveDate};
+use num::BigInt;
+
+use crate::{
+    attendance::AttendanceRecord,
+    schemas::{AbsenceKind, ReportFilter, ReportParam, ReportRequest},
+};
+
+use super::html_table::{self, Table};
+
+pub const REPORT_NAME: &str = "studentattendancegrades";
+
+/// The beginnings of the month names, in the order of the months. "Март" goes before "май", so
+/// that it is not taken for May.
+const MONTHS: [&str; 12] = [
+    "январ",
+    "феврал",
+    "март",
+    "апрел",
+    "ма",
+    "июн",
+    "июл",
+    "август",
+    "сентябр",
+    "октябр",
+    "ноябр",
+    "декабр",
+];
+
+/// The "Отчёт об успеваемости и посещаемости" report: the marks and the absence marks of the
+/// student for every lesson of the period, including the lessons without assignments, which the
+/// diary does not list. Every day of the period is a column, with the month above the days.
+#[derive(Debug)]
+pub struct StudentAttendanceGradesReport {
+    /// The absences in the order of the subjects and then the days.
+    pub absences: Vec<AttendanceRecord>,
+}
+
+pub fn request(
+    student_id: &BigInt,
+    year_id: &BigInt,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> ReportRequest {
+    ReportRequest {
+        filters: vec![
+            ReportFilter {
+                id: "SID".to_owned(),
+                value: student_id.to_string(),
+                text: String::new(),
+            },
+            ReportFilter {
+                id: "period".to_owned(),
+                value: format!("{}T00:00:00 - {}T00:00:00", start, end),
+                text: String::new(),
+            },
+        ],
+        params: vec![ReportParam {
+            name: "SCHOOLYEARID".to_owned(),
+            value: year_id.to_string(),
+        }],
+    }
+}
+
+fn month(header: &str) -> Option<u32> {
+    let header = header.to_lowercase();
+    let position = MONTHS.iter().position(|month| {
+        header
+            .split(|character: char| !character.is_alphabetic())
+            .any(|word| word.starts_with(month))
+    })?;
+    Some(position as u32 + 1)
+}
+
+/// Dates the column by the day in the last header row and the month in one of the rows above it.
+/// The header has no years, so the date is looked for in the requested period.
+fn column_date(
+    header_rows: &[Vec<String>],
+    column: usize,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Option<NaiveDate> {
+    let (day_row, month_rows) = header_rows.split_last()?;
+    let day = day_row.get(column)?.trim().parse().ok()?;
+    let month = month_rows.iter().find_map(|row| month(row.get(column)?))?;
+    (start.year()..=end.year())
+        .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .find(|date| (start..=end).contains(date))
+}
+
+/// The absence marks of the cell. A cell has the marks for the work done on the lesson as well,
+/// so only the standard abbreviations are taken: the ones schools define themselves can not be
+/// told apart from the marks like "зач".
+fn absence_kinds(cell: &str) -> impl Iterator<Item = AbsenceKind> + '_ {
+    cell.split(|character: char| character.is_whitespace() || character == ',')
+        .filter(|abbreviation| !abbreviation.is_empty())
+        .map(|abbreviation| AbsenceKind::from(abbreviation.to_owned()))
+        .filter(|kind| !matches!(kind, AbsenceKind::Other(_)))
+}
+
+impl StudentAttendanceGradesReport {
+    /// Parses the report generated for the period from `start` to `end`. `None` if the report
+    /// does not contain the table with the days of the period.
+    pub fn parse(html: &str, start: NaiveDate, end: NaiveDate) -> Option<Self> {
+        html_table::tables(html)
+            .iter()
+            .find_map(|table| Self::from_table(table, start, end))
+    }
+
+    fn from_table(table: &Table, start: NaiveDate, end: NaiveDate) -> Option<Self> {
+        let (header_rows, body) = html_table::split_header_rows(table, "предмет")?;
+        let column_dates: Vec<Option<NaiveDate>> = (0..header_rows.last()?.len())
+            .map(|column| column_date(header_rows, column, start, end))
+            .collect();
+        if column_dates.iter().all(Option::is_none) {
+            return None;
+        }
+        let absences = body
+            .iter()
+            .filter(|row| row.first().is_some_and(|subject| !subject.is_empty()))
+            .flat_map(|row| {
+                row.iter()
+                    .zip(&column_dates)
+                    .filter_map(|(cell, date)| Some((cell, (*date)?)))
+                    .flat_map(move |(cell, date)| {
+                        absence_kinds(cell).map(move |kind| AttendanceRecord {
+                            kind,
+                            date,
+                            subject_name: row[0].clone(),
+                        })
+                    })
+            })
+            .collect();
+        Some(Self { absences })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::schemas::AbsenceKind;
+
+    use super::StudentAttendanceGradesReport;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn absences_are_dated_by_the_month_and_the_day() {
+        let report = StudentAttendanceGradesReport::parse(
+            "<table>\
+                <tr><th rowspan=\"2\">Предмет</th><th colspan=\"2\">Декабрь</th>\
+                    <th>Январь</th><th rowspan=\"2\">Средняя оценка</th></tr>\
+                <tr><th>29</th><th>30</th><th>9</th></tr>\
+                <tr><td>Алгебра</td><td>5 Н</td><td></td><td>зач</td><td>5</td></tr>\
+                <tr><td>Физика</td><td></td><td>Б</td><td>4, О</td><td>4</td></tr>\
+            </table>",
+            date(2022, 12, 26),
+            date(2023, 1, 15),
+        )
+        .unwrap();
+        let absences: Vec<_> = report
+            .absences
+            .iter()
+            .map(|record| (record.subject_name.as_str(), record.date, &record.kind))
+            .collect();
+        assert_eq!(
+            absences,
+            [
+                ("Алгебра", date(2022, 12, 29), &AbsenceKind::Absent),
+                ("Физика", date(2022, 12, 30), &AbsenceKind::Ill),
+                ("Физика", date(2023, 1, 9), &AbsenceKind::Late),
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_without_days_are_skipped() {
+        assert!(StudentAttendanceGradesReport::parse(
+            "<table><tr><th>Предмет</th><th>Средняя оценка</th></tr>\
+                <tr><td>Алгебра</td><td>5</td></tr></table>",
+            date(2022, 9, 1),
+            date(2022, 9, 30),
+        )
+        .is_none());
+    }
+}
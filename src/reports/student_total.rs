@@ -0,0 +1,139 @@
+use num::BigInt;
+
+use crate::schemas::{ReportFilter, ReportParam, ReportRequest};
+
+use super::html_table::{self, Table};
+
+pub const REPORT_NAME: &str = "studenttotal";
+
+/// The "Итоговые отметки" report: the average marks of the student for every term and the marks
+/// the subjects were finished with.
+#[derive(Debug)]
+pub struct StudentTotalReport {
+    /// The names of the terms, in the order of the columns.
+    pub terms: Vec<String>,
+    pub subjects: Vec<SubjectTotals>,
+}
+
+#[derive(Debug)]
+pub struct SubjectTotals {
+    pub subject: String,
+    /// One value for every term in `StudentTotalReport::terms`. `None` if there were no marks.
+    pub term_averages: Vec<Option<f64>>,
+    /// The marks are strings, because besides the numbers they can be "зач", "н/а" and so on.
+    pub year_mark: Option<String>,
+    pub exam_mark: Option<String>,
+    pub final_mark: Option<String>,
+}
+
+enum Column {
+    Subject,
+    Term,
+    Year,
+    Exam,
+    Final,
+}
+
+impl Column {
+    fn from_header(header: &str) -> Self {
+        let header = header.to_lowercase();
+        if header.starts_with("предмет") {
+            Self::Subject
+        } else if header.contains("экзамен") {
+            Self::Exam
+        } else if header.contains("итог") {
+            Self::Final
+        } else if header
+            .split(|character: char| !character.is_alphanumeric())
+            .any(|word| word.starts_with("год"))
+        {
+            // The word itself is checked, so that the half-years ("1 полугодие") are terms.
+            Self::Year
+        } else {
+            Self::Term
+        }
+    }
+}
+
+pub fn request(student_id: &BigInt, year_id: &BigInt) -> ReportRequest {
+    ReportRequest {
+        filters: vec![ReportFilter {
+            id: "SID".to_owned(),
+            value: student_id.to_string(),
+            text: String::new(),
+        }],
+        params: vec![ReportParam {
+            name: "SCHOOLYEARID".to_owned(),
+            value: year_id.to_string(),
+        }],
+    }
+}
+
+impl StudentTotalReport {
+    /// Parses the generated report. `None` if the report does not contain the table of marks.
+    pub fn parse(html: &str) -> Option<Self> {
+        html_table::tables(html).iter().find_map(Self::from_table)
+    }
+
+    fn from_table(table: &Table) -> Option<Self> {
+        let (header, body) = html_table::split_header(table, "предмет")?;
+        let columns: Vec<Column> = header
+            .iter()
+            .map(|header| Column::from_header(header))
+            .collect();
+        let terms = header
+            .iter()
+            .zip(&columns)
+            .filter(|(_header, column)| matches!(column, Column::Term))
+            .map(|(header, _column)| header.clone())
+            .collect();
+        let subjects = body
+            .iter()
+            .filter(|row| row.first().is_some_and(|subject| !subject.is_empty()))
+            .map(|row| {
+                let mut totals = SubjectTotals {
+                    subject: String::new(),
+                    term_averages: Vec::new(),
+                    year_mark: None,
+                    exam_mark: None,
+                    final_mark: None,
+                };
+                for (cell, column) in row.iter().zip(&columns) {
+                    match column {
+                        Column::Subject => totals.subject = cell.clone(),
+                        Column::Term => totals.term_averages.push(html_table::number(cell)),
+                        Column::Year => totals.year_mark = html_table::non_empty(cell),
+                        Column::Exam => totals.exam_mark = html_table::non_empty(cell),
+                        Column::Final => totals.final_mark = html_table::non_empty(cell),
+                    }
+                }
+                totals
+            })
+            .collect();
+        Some(Self { terms, subjects })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StudentTotalReport;
+
+    #[test]
+    fn half_years_are_terms() {
+        let report = StudentTotalReport::parse(
+            "<table>\
+                <tr><th>Предмет</th><th>1 полугодие</th><th>2 полугодие</th>\
+                    <th>Годовая</th><th>Экзамен</th><th>Итоговая</th></tr>\
+                <tr><td>Алгебра</td><td>4,5</td><td>-</td><td>5</td><td></td><td>5</td></tr>\
+            </table>",
+        )
+        .unwrap();
+        assert_eq!(report.terms, ["1 полугодие", "2 полугодие"]);
+        let algebra = &report.subjects[0];
+        assert_eq!(algebra.subject, "Алгебра");
+        assert_eq!(algebra.term_averages, [Some(4.5), None]);
+        assert_eq!(algebra.year_mark.as_deref(), Some("5"));
+        assert_eq!(algebra.exam_mark, None);
+        assert_eq!(algebra.final_mark.as_deref(), Some("5"));
+    }
+}
@@ -1,9 +1,14 @@
 // Resolves a bug in clippy that lets it invoke `::use_self` on a macro-generated code.
 #![allow(clippy::use_self)]
 
-use num::BigInt;
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use num::{BigInt, ToPrimitive};
 use serde::{Deserialize, Serialize};
 
+use crate::{json, server_dates};
+
 /// Used to distinguish between the Gosuslugi log-on and username-password log-on.
 #[repr(u8)]
 #[derive(Serialize)]
@@ -13,9 +18,10 @@ pub enum LoginType {
 }
 
 #[repr(u8)]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EducationalInstitutionType {
     PreSchool = 1,
+    #[default]
     School = 2,
     /// An institution used simultaneously with any other insitution. Examples: sports school, arts
     /// school. This does not have to be a distinct institution though, so the institutions with
@@ -24,14 +30,21 @@ pub enum EducationalInstitutionType {
     Additional = 3,
 }
 
-impl Default for EducationalInstitutionType {
-    fn default() -> Self {
-        Self::School
+impl TryFrom<u8> for EducationalInstitutionType {
+    type Error = u8;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            1 => Ok(Self::PreSchool),
+            2 => Ok(Self::School),
+            3 => Ok(Self::Additional),
+            _ => Err(id),
+        }
     }
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct PreAuthData {
+pub(crate) struct PreAuthData {
     /// I have no idea what this is.
     pub lt: String,
     /// I have no idea what this is.
@@ -42,24 +55,202 @@ pub struct PreAuthData {
 
 #[derive(Deserialize, Serialize)]
 pub struct SchoolInfo {
-    #[serde(rename(serialize = "cid", deserialize = "countryId"))]
+    #[serde(
+        rename(serialize = "cid", deserialize = "countryId"),
+        with = "json::big_int"
+    )]
     pub country_id: BigInt,
-    #[serde(rename(serialize = "sid", deserialize = "stateId"))]
+    #[serde(
+        rename(serialize = "sid", deserialize = "stateId"),
+        with = "json::big_int"
+    )]
     pub state_id: BigInt,
-    #[serde(rename(serialize = "pid", deserialize = "municipalityDistrictId"))]
+    #[serde(
+        rename(serialize = "pid", deserialize = "municipalityDistrictId"),
+        with = "json::big_int"
+    )]
     pub pid: BigInt,
-    #[serde(rename(serialize = "cn", deserialize = "cityId"))]
+    #[serde(
+        rename(serialize = "cn", deserialize = "cityId"),
+        with = "json::big_int"
+    )]
     pub city_id: BigInt,
     #[serde(rename(serialize = "sft"), default)]
     pub educational_institution_type: EducationalInstitutionType,
-    #[serde(rename(serialize = "scid", deserialize = "id"))]
+    #[serde(rename(serialize = "scid", deserialize = "id"), with = "json::big_int")]
     pub id: BigInt,
     #[serde(skip_serializing)]
     pub name: String,
 }
 
 #[derive(Serialize)]
-pub struct AuthRequest<'auth_request, Username> {
+pub(crate) struct SchoolSearchRequest<'school_search_request> {
+    /// A part of the school name.
+    pub name: &'school_search_request str,
+    #[serde(rename(serialize = "withAddress"))]
+    pub with_address: bool,
+    pub skip: u32,
+    pub take: u32,
+}
+
+/// A level of the address hierarchy the schools are grouped by. The levels go in the order of
+/// declaration, every one of them being narrowed by the previous ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressLevel {
+    Country,
+    State,
+    MunicipalityDistrict,
+    City,
+    EducationalInstitutionType,
+    School,
+}
+
+impl AddressLevel {
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Self::Country => Some(Self::State),
+            Self::State => Some(Self::MunicipalityDistrict),
+            Self::MunicipalityDistrict => Some(Self::City),
+            Self::City => Some(Self::EducationalInstitutionType),
+            Self::EducationalInstitutionType => Some(Self::School),
+            Self::School => None,
+        }
+    }
+
+    /// The name of the level in the requests.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Country => "cid",
+            Self::State => "sid",
+            Self::MunicipalityDistrict => "pid",
+            Self::City => "cn",
+            Self::EducationalInstitutionType => "sft",
+            Self::School => "scid",
+        }
+    }
+}
+
+/// The choices made while going down the address hierarchy.
+#[derive(Serialize, Default, Debug)]
+pub struct AddressSelection {
+    #[serde(
+        rename(serialize = "cid"),
+        with = "json::optional_big_int",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub country_id: Option<BigInt>,
+    #[serde(
+        rename(serialize = "sid"),
+        with = "json::optional_big_int",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub state_id: Option<BigInt>,
+    #[serde(
+        rename(serialize = "pid"),
+        with = "json::optional_big_int",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub municipality_district_id: Option<BigInt>,
+    #[serde(
+        rename(serialize = "cn"),
+        with = "json::optional_big_int",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub city_id: Option<BigInt>,
+    #[serde(
+        rename(serialize = "sft"),
+        serialize_with = "serialize_educational_institution_type_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub educational_institution_type: Option<EducationalInstitutionType>,
+}
+
+/// The address options are requested with the numeric id of the institution type, unlike the
+/// log-in.
+fn serialize_educational_institution_type_id<S: serde::Serializer>(
+    educational_institution_type: &Option<EducationalInstitutionType>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match educational_institution_type {
+        Some(educational_institution_type) => {
+            serializer.serialize_u8(*educational_institution_type as u8)
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+impl AddressSelection {
+    /// Remembers the option chosen on the `level` and forgets the choices made on the levels
+    /// below it, since they may be not allowed anymore. Returns `false` if the option cannot be
+    /// chosen on this level (the `AddressLevel::School` level is finished with `school_info`
+    /// instead).
+    pub fn select(&mut self, level: AddressLevel, option_id: BigInt) -> bool {
+        match level {
+            AddressLevel::Country => self.country_id = Some(option_id),
+            AddressLevel::State => self.state_id = Some(option_id),
+            AddressLevel::MunicipalityDistrict => self.municipality_district_id = Some(option_id),
+            AddressLevel::City => self.city_id = Some(option_id),
+            AddressLevel::EducationalInstitutionType => {
+                match option_id
+                    .to_u8()
+                    .and_then(|id| EducationalInstitutionType::try_from(id).ok())
+                {
+                    Some(educational_institution_type) => {
+                        self.educational_institution_type = Some(educational_institution_type);
+                    }
+                    None => return false,
+                }
+            }
+            AddressLevel::School => return false,
+        }
+        let mut lower_level = level.next();
+        while let Some(level) = lower_level {
+            match level {
+                AddressLevel::Country => self.country_id = None,
+                AddressLevel::State => self.state_id = None,
+                AddressLevel::MunicipalityDistrict => self.municipality_district_id = None,
+                AddressLevel::City => self.city_id = None,
+                AddressLevel::EducationalInstitutionType => {
+                    self.educational_institution_type = None;
+                }
+                AddressLevel::School => {}
+            }
+            lower_level = level.next();
+        }
+        true
+    }
+
+    /// Makes a `SchoolInfo` out of the `school` chosen on the `AddressLevel::School` level.
+    /// `None` if some of the levels above were not chosen.
+    pub fn school_info(&self, school: NamedEntity) -> Option<SchoolInfo> {
+        Some(SchoolInfo {
+            country_id: self.country_id.clone()?,
+            state_id: self.state_id.clone()?,
+            pid: self.municipality_district_id.clone()?,
+            city_id: self.city_id.clone()?,
+            educational_institution_type: self.educational_institution_type?,
+            id: school.id,
+            name: school.name,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct AddressOptionsRequest<'address_options_request> {
+    #[serde(flatten)]
+    pub selection: &'address_options_request AddressSelection,
+    /// The key of the level to list the options of.
+    #[serde(rename(serialize = "lastname"))]
+    pub level: &'static str,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AddressOptionsResponse {
+    pub items: Vec<NamedEntity>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct AuthRequest<'auth_request, Username> {
     #[serde(rename(serialize = "loginType"))]
     pub login_type: LoginType,
     #[serde(rename(serialize = "un"))]
@@ -78,31 +269,1063 @@ pub struct AuthRequest<'auth_request, Username> {
 }
 
 #[derive(Deserialize)]
-pub struct AuthResponse {
+pub(crate) struct AuthResponse {
     #[serde(rename(deserialize = "at"))]
     pub auth_token: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Student {
-    #[serde(rename(serialize = "studentId"))]
+    #[serde(rename(deserialize = "studentId"), with = "json::big_int")]
     pub student_id: BigInt,
+    #[serde(rename(deserialize = "nickName"))]
+    pub name: String,
+    #[serde(rename(deserialize = "className"))]
+    pub class_name: Option<String>,
 }
 
 #[derive(Deserialize)]
-pub struct DiaryInfo {
+pub(crate) struct DiaryInfo {
+    /// The student themselves or all the children of a parent.
     pub students: Vec<Student>,
-    #[serde(rename(serialize = "currentStudentId"))]
+    /// The id of the student selected by default, one of the `students`.
+    #[serde(rename(deserialize = "currentStudentId"), with = "json::big_int")]
     pub current_student_id: BigInt,
 }
 
 #[derive(Deserialize)]
-pub struct CurrentYear {
+pub(crate) struct CurrentYear {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchoolYear {
+    #[serde(with = "json::big_int")]
     pub id: BigInt,
+    /// Looks like "2022/2023".
+    pub name: String,
+    #[serde(
+        rename(deserialize = "startDate"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub start_date: NaiveDate,
+    #[serde(
+        rename(deserialize = "endDate"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub end_date: NaiveDate,
+}
+
+impl SchoolYear {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct YearIdRequest<'year_id_request> {
+    #[serde(
+        rename(serialize = "yearId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub year_id: &'year_id_request BigInt,
+}
+
+/// A reporting period of a school year (a quarter, a trimester or a half-year, depending on the
+/// school).
+#[derive(Debug, Deserialize)]
+pub struct Term {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    /// Looks like "1 четверть".
+    pub name: String,
+    #[serde(
+        rename(deserialize = "startDate"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub start_date: NaiveDate,
+    #[serde(
+        rename(deserialize = "endDate"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub end_date: NaiveDate,
+}
+
+impl Term {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+
+    /// Finds the term the `item` (a date, a day, a lesson or an assignment) falls in. A mark has
+    /// no date of its own, so its term is the term of the assignment it was put for.
+    pub fn find<'terms>(terms: &'terms [Self], item: &impl Dated) -> Option<&'terms Self> {
+        let date = item.date();
+        terms.iter().find(|term| term.contains(date))
+    }
+}
+
+/// Something that happened on a specific day. `Mark` is not one of them: the server does not tell
+/// when a mark was put, so the `Assignment` holding the mark has to be used instead.
+pub trait Dated {
+    fn date(&self) -> NaiveDate;
+}
+
+impl Dated for NaiveDate {
+    fn date(&self) -> NaiveDate {
+        *self
+    }
+}
+
+impl Dated for Day {
+    fn date(&self) -> NaiveDate {
+        self.date
+    }
+}
+
+impl Dated for Lesson {
+    fn date(&self) -> NaiveDate {
+        self.day
+    }
+}
+
+impl Dated for Assignment {
+    fn date(&self) -> NaiveDate {
+        self.due_date
+    }
 }
 
 #[derive(Deserialize)]
+pub(crate) struct AssignmentType {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    pub name: String,
+}
+
+/// Used for the diary, the past mandatory assignments and the timetable.
+#[derive(Serialize)]
+pub(crate) struct DiaryRequest<'diary_request> {
+    #[serde(
+        rename(serialize = "studentId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub student_id: &'diary_request BigInt,
+    #[serde(
+        rename(serialize = "yearId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub year_id: &'diary_request BigInt,
+    #[serde(rename(serialize = "weekStart"))]
+    pub week_start: NaiveDate,
+    #[serde(rename(serialize = "weekEnd"))]
+    pub week_end: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Diary {
+    #[serde(
+        rename(deserialize = "weekStart"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub week_start: NaiveDate,
+    #[serde(
+        rename(deserialize = "weekEnd"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub week_end: NaiveDate,
+    #[serde(rename(deserialize = "weekDays"))]
+    pub days: Vec<Day>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Day {
+    #[serde(deserialize_with = "server_dates::date")]
+    pub date: NaiveDate,
+    pub lessons: Vec<Lesson>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Lesson {
+    #[serde(rename(deserialize = "classmeetingId"), with = "json::big_int")]
+    pub class_meeting_id: BigInt,
+    #[serde(deserialize_with = "server_dates::date")]
+    pub day: NaiveDate,
+    /// The number of the lesson in the day, starting from 1.
+    #[serde(with = "json::big_int")]
+    pub number: BigInt,
+    pub room: Option<String>,
+    #[serde(
+        rename(deserialize = "startTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub start_time: NaiveTime,
+    #[serde(
+        rename(deserialize = "endTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub end_time: NaiveTime,
+    #[serde(rename(deserialize = "subjectName"))]
+    pub subject_name: String,
+    #[serde(default)]
+    pub assignments: Vec<Assignment>,
+    /// The absence mark of the student, if there is one.
+    #[serde(rename(deserialize = "attendance"), default)]
+    pub absence: Option<AbsenceKind>,
+}
+
+/// An absence mark put on a lesson. The server sends them as their abbreviations.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(from = "String")]
+pub enum AbsenceKind {
+    /// "Н": was not present without a reason.
+    Absent,
+    /// "Б": was ill.
+    Ill,
+    /// "О": was late.
+    Late,
+    /// "П": was not present for a good reason.
+    Excused,
+    /// Any other abbreviation (schools can define their own ones).
+    Other(String),
+}
+
+impl From<String> for AbsenceKind {
+    fn from(abbreviation: String) -> Self {
+        match abbreviation.trim().to_uppercase().as_str() {
+            "Н" => Self::Absent,
+            "Б" => Self::Ill,
+            "О" => Self::Late,
+            "П" | "УП" => Self::Excused,
+            _ => Self::Other(abbreviation),
+        }
+    }
+}
+
+impl AbsenceKind {
+    /// Whether the lesson was missed (being late does not count as missing the lesson).
+    pub const fn is_missed(&self) -> bool {
+        !matches!(self, Self::Late)
+    }
+}
+
+#[derive(Debug, Deserialize)]
 pub struct Assignment {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    #[serde(rename(deserialize = "typeId"), with = "json::big_int")]
+    pub type_id: BigInt,
+    /// The name of the assignment type, resolved through `UserData::assignment_types`. `None` if
+    /// the server did not list the type at the log-in.
+    #[serde(skip)]
+    pub type_name: Option<String>,
+    /// The short text of the assignment (the homework itself, usually).
+    #[serde(rename(deserialize = "assignmentName"))]
+    pub name: String,
+    #[serde(
+        rename(deserialize = "dueDate"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub due_date: NaiveDate,
+    pub mark: Option<Mark>,
+    /// Only sent with the past mandatory assignments; in the diary the subject is known from the
+    /// lesson.
+    #[serde(rename(deserialize = "subjectName"), default)]
+    pub subject_name: Option<String>,
+}
+
+impl Assignment {
+    pub fn resolve_type_name(&mut self, assignment_types: &HashMap<BigInt, String>) {
+        self.type_name = assignment_types.get(&self.type_id).cloned();
+    }
+}
+
+/// Has no date: use the `Assignment` the mark belongs to (with `Term::find`, for example) when
+/// the mark has to be placed in time.
+#[derive(Debug, Deserialize)]
+pub struct Mark {
+    /// `None` when only a duty mark (a dot) was put.
+    #[serde(rename(deserialize = "mark"), with = "json::optional_big_int", default)]
+    pub value: Option<BigInt>,
+    /// Whether the assignment was not done in time (it is shown as a dot in the web interface).
+    #[serde(rename(deserialize = "dutyMark"), default)]
+    pub is_duty: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StudentIdRequest<'student_id_request> {
+    #[serde(
+        rename(serialize = "studentId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub student_id: &'student_id_request BigInt,
+}
+
+/// A full version of `Assignment`.
+#[derive(Debug, Deserialize)]
+pub struct AssignmentDetails {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    #[serde(rename(deserialize = "typeId"), with = "json::big_int")]
+    pub type_id: BigInt,
+    /// Same as `Assignment::type_name`.
+    #[serde(skip)]
+    pub type_name: Option<String>,
+    #[serde(rename(deserialize = "assignmentName"))]
+    pub name: String,
+    /// The full text of the assignment. Can contain HTML.
+    pub description: Option<String>,
+    #[serde(rename(deserialize = "subjectGroup"))]
+    pub subject: NamedEntity,
+    pub teacher: NamedEntity,
+    /// How much the mark for this assignment affects the average mark.
+    #[serde(with = "json::big_int")]
+    pub weight: BigInt,
+    #[serde(rename(deserialize = "date"), deserialize_with = "server_dates::date")]
+    pub due_date: NaiveDate,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+impl AssignmentDetails {
+    pub fn resolve_type_name(&mut self, assignment_types: &HashMap<BigInt, String>) {
+        self.type_name = assignment_types.get(&self.type_id).cloned();
+    }
+}
+
+/// Anything the server describes with just an id and a name (a teacher, a subject, a class).
+#[derive(Debug, Clone, Deserialize)]
+pub struct NamedEntity {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Attachment {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    /// Mail attachments have it sent as just "name".
+    #[serde(rename(deserialize = "originalFileName"), alias = "name")]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct AttachmentsRequest<'attachments_request> {
+    #[serde(
+        rename(serialize = "assignId"),
+        serialize_with = "json::big_ints::serialize"
+    )]
+    pub assignment_ids: &'attachments_request [BigInt],
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignmentAttachments {
+    #[serde(rename(deserialize = "assignmentId"), with = "json::big_int")]
+    pub assignment_id: BigInt,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct AnnouncementsRequest {
+    /// How many of the latest announcements to get. -1 means all of them.
+    pub take: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Announcement {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    /// The title.
+    pub name: String,
+    pub author: Author,
+    #[serde(
+        rename(deserialize = "postDate"),
+        deserialize_with = "server_dates::date_time"
+    )]
+    pub post_date: NaiveDateTime,
+    /// Who the announcement is meant for, in a human-readable form ("Всем", for example).
+    #[serde(rename(deserialize = "recipientInfo"))]
+    pub recipient_group: Option<String>,
+    /// The body of the announcement. Contains HTML.
+    #[serde(rename(deserialize = "description"))]
+    pub content: String,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Author {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    #[serde(rename(deserialize = "fio"))]
+    pub full_name: String,
+    #[serde(rename(deserialize = "nickName"))]
+    pub nickname: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchoolCard {
+    #[serde(rename(deserialize = "commonInfo"))]
+    pub common_info: SchoolCommonInfo,
+    #[serde(rename(deserialize = "contactInfo"))]
+    pub contact_info: SchoolContactInfo,
+    #[serde(rename(deserialize = "managementInfo"))]
+    pub management_info: SchoolManagementInfo,
+}
+
+impl SchoolCard {
+    /// The juridical address if it is set, the post address otherwise.
+    pub fn address(&self) -> Option<&str> {
+        self.contact_info
+            .juridical_address
+            .as_deref()
+            .filter(|address| !address.is_empty())
+            .or(self.contact_info.post_address.as_deref())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchoolCommonInfo {
+    #[serde(rename(deserialize = "schoolName"))]
+    pub name: String,
+    #[serde(rename(deserialize = "fullSchoolName"))]
+    pub full_name: Option<String>,
+    pub about: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchoolContactInfo {
+    #[serde(rename(deserialize = "juridicalAddress"))]
+    pub juridical_address: Option<String>,
+    #[serde(rename(deserialize = "postAddress"))]
+    pub post_address: Option<String>,
+    /// Can contain several phone numbers in a free form.
+    #[serde(rename(deserialize = "phones"))]
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[serde(rename(deserialize = "web"))]
+    pub website: Option<String>,
+}
+
+/// Contains full names of the school management.
+#[derive(Debug, Deserialize)]
+pub struct SchoolManagementInfo {
+    pub director: Option<String>,
+    /// Deputy director for administrative and economic work.
+    #[serde(rename(deserialize = "principalAHC"))]
+    pub principal_ahc: Option<String>,
+    /// Deputy director for information technologies.
+    #[serde(rename(deserialize = "principalIT"))]
+    pub principal_it: Option<String>,
+    /// Deputy director for educational work.
+    #[serde(rename(deserialize = "principalUVR"))]
+    pub principal_uvr: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct StudentYearRequest<'student_year_request> {
+    #[serde(
+        rename(serialize = "studentId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub student_id: &'student_year_request BigInt,
+    #[serde(
+        rename(serialize = "yearId"),
+        serialize_with = "json::big_int::serialize"
+    )]
+    pub year_id: &'student_year_request BigInt,
+}
+
+/// The marks a subject is finished with. The marks are strings, because besides the numbers they
+/// can be "зач", "н/а", "осв" and so on.
+#[derive(Debug, Deserialize)]
+pub struct SubjectFinalMarks {
+    pub subject: NamedEntity,
+    #[serde(rename(deserialize = "termMarks"), default)]
+    pub term_marks: Vec<TermMark>,
+    #[serde(rename(deserialize = "yearMark"))]
+    pub year_mark: Option<String>,
+    #[serde(rename(deserialize = "examMark"))]
+    pub exam_mark: Option<String>,
+    /// The mark that goes to the certificate.
+    #[serde(rename(deserialize = "finalMark"))]
+    pub final_mark: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TermMark {
+    #[serde(rename(deserialize = "termId"), with = "json::big_int")]
+    pub term_id: BigInt,
+    pub mark: Option<String>,
+    /// The weighted average of the marks received during the term.
+    pub average: Option<f64>,
+}
+
+/// What a report should be generated for. Both the filters and the parameters are specific to
+/// the report.
+#[derive(Serialize, Default, Debug)]
+pub struct ReportRequest {
+    #[serde(rename(serialize = "selectedData"))]
+    pub filters: Vec<ReportFilter>,
+    pub params: Vec<ReportParam>,
+}
+
+/// A choice made in one of the report filters (the student, the term, the period).
+#[derive(Serialize, Debug)]
+pub struct ReportFilter {
+    #[serde(rename(serialize = "filterId"))]
+    pub id: String,
+    #[serde(rename(serialize = "filterValue"))]
+    pub value: String,
+    /// The human-readable representation of the value. Can be left empty.
+    #[serde(rename(serialize = "filterText"))]
+    pub text: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReportParam {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReportQueueResponse {
+    #[serde(rename(deserialize = "taskId"), with = "json::big_int")]
+    pub task_id: BigInt,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all(deserialize = "lowercase"))]
+pub enum ReportTaskStatus {
+    #[serde(alias = "queued")]
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ReportTask {
+    pub status: ReportTaskStatus,
+    /// The id of the file with the generated report. Only set when the task is completed.
+    #[serde(
+        rename(deserialize = "fileId"),
+        with = "json::optional_big_int",
+        default
+    )]
+    pub file_id: Option<BigInt>,
+    #[serde(rename(deserialize = "errorMessage"), default)]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailBox {
+    Inbox,
+    Sent,
+    Drafts,
+    Deleted,
+}
+
+impl MailBox {
+    /// The value of the mail box filter in the requests.
+    pub const fn filter_value(self) -> &'static str {
+        match self {
+            Self::Inbox => "Inbox",
+            Self::Sent => "Sent",
+            Self::Drafts => "Draft",
+            Self::Deleted => "Deleted",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct MailListRequest {
+    /// The mail uses the same filter format as the reports.
+    #[serde(rename(serialize = "filterContext"))]
+    pub filter_context: ReportRequest,
+    /// The fields of the messages to include in the response.
+    pub fields: &'static [&'static str],
+    /// Starts from 1.
+    pub page: u32,
+    #[serde(rename(serialize = "pageSize"))]
+    pub page_size: u32,
+    pub order: MailOrder,
+}
+
+#[derive(Serialize)]
+pub(crate) struct MailOrder {
+    #[serde(rename(serialize = "fieldId"))]
+    pub field_id: &'static str,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MailPage {
+    #[serde(rename(deserialize = "rows"))]
+    pub entries: Vec<MailEntry>,
+    /// How many messages there are in the mail box on all the pages.
+    #[serde(rename(deserialize = "totalItems"))]
+    pub total: usize,
+}
+
+/// A message as it is shown in the mail box list.
+#[derive(Debug, Deserialize)]
+pub struct MailEntry {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    /// The full name of the sender.
+    pub author: String,
+    pub subject: String,
+    #[serde(deserialize_with = "server_dates::date_time")]
+    pub sent: NaiveDateTime,
+    #[serde(rename(deserialize = "read"), default)]
+    pub is_read: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    pub author: NamedEntity,
+    #[serde(rename(deserialize = "to"), default)]
+    pub recipients: Vec<NamedEntity>,
+    #[serde(rename(deserialize = "copyTo"), default)]
+    pub copy_recipients: Vec<NamedEntity>,
+    pub subject: String,
+    /// The body of the message. Contains HTML.
+    pub text: String,
+    #[serde(deserialize_with = "server_dates::date_time")]
+    pub sent: NaiveDateTime,
+    #[serde(rename(deserialize = "fileAttachments"), default)]
+    pub attachments: Vec<Attachment>,
+}
+
+/// A message to be sent.
+#[derive(Debug, Default)]
+pub struct NewMessage {
+    /// The ids of the users (see the address book).
+    pub recipients: Vec<BigInt>,
+    pub copy_recipients: Vec<BigInt>,
+    pub subject: String,
+    /// Can contain HTML.
+    pub text: String,
+    /// The files to upload with the message.
+    pub attachments: Vec<NewAttachment>,
+    /// The ids of the files that are already on the server (the attachments of a forwarded
+    /// message, for example).
+    pub attached_file_ids: Vec<BigInt>,
+}
+
+impl NewMessage {
+    /// Makes a reply to the author of the `original` message with the original text quoted.
+    pub fn reply(original: &Message, text: String) -> Self {
+        Self {
+            recipients: vec![original.author.id.clone()],
+            subject: prefixed_subject("Re: ", &original.subject),
+            text: quoted(text, original),
+            ..Self::default()
+        }
+    }
+
+    /// Makes a message with the `original` message quoted and its attachments attached.
+    pub fn forward(original: &Message, recipients: Vec<BigInt>, text: String) -> Self {
+        Self {
+            recipients,
+            subject: prefixed_subject("Fwd: ", &original.subject),
+            text: quoted(text, original),
+            attached_file_ids: original
+                .attachments
+                .iter()
+                .map(|attachment| attachment.id.clone())
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Does not stack the prefixes when replying to a reply.
+fn prefixed_subject(prefix: &str, subject: &str) -> String {
+    if subject.starts_with(prefix) {
+        subject.to_owned()
+    } else {
+        format!("{}{}", prefix, subject)
+    }
+}
+
+fn quoted(text: String, original: &Message) -> String {
+    format!(
+        "{}<br><br>{}, {}:<blockquote>{}</blockquote>",
+        text,
+        original.sent.format("%d.%m.%Y %H:%M"),
+        original.author.name,
+        original.text
+    )
+}
+
+#[derive(Debug)]
+pub struct NewAttachment {
+    pub file_name: String,
+    pub contents: Vec<u8>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SendMessageRequest<'send_message_request> {
+    #[serde(serialize_with = "json::big_ints::serialize")]
+    pub to: &'send_message_request [BigInt],
+    #[serde(rename(serialize = "cc"), serialize_with = "json::big_ints::serialize")]
+    pub copy_to: &'send_message_request [BigInt],
+    pub subject: &'send_message_request str,
+    pub text: &'send_message_request str,
+    #[serde(
+        rename(serialize = "fileAttachments"),
+        serialize_with = "json::big_ints::serialize"
+    )]
+    pub file_ids: &'send_message_request [BigInt],
+}
+
+/// The response to creating anything: uploading a file, sending a message, starting a topic.
+#[derive(Deserialize)]
+pub(crate) struct CreatedEntity {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+}
+
+/// The groups the mail recipients are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientGroup {
+    Teachers,
+    ClassTeacher,
+    Administration,
+    Parents,
+    Students,
+}
+
+impl RecipientGroup {
+    pub const ALL: [Self; 5] = [
+        Self::Teachers,
+        Self::ClassTeacher,
+        Self::Administration,
+        Self::Parents,
+        Self::Students,
+    ];
+
+    /// The value of the group in the requests.
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Teachers => "teachers",
+            Self::ClassTeacher => "classteacher",
+            Self::Administration => "administration",
+            Self::Parents => "parents",
+            Self::Students => "students",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct RecipientsRequest {
+    pub group: &'static str,
+}
+
+/// All the users the mail can be sent to.
+#[derive(Debug)]
+pub struct AddressBook {
+    /// Groups the server did not allow to write to are left out.
+    pub groups: Vec<(RecipientGroup, Vec<NamedEntity>)>,
+}
+
+impl AddressBook {
+    /// Finds the recipients whose names contain every word of the `name` (ignoring case), so
+    /// "иван петр" finds "Петров Иван Сергеевич".
+    pub fn search(&self, name: &str) -> Vec<(RecipientGroup, &NamedEntity)> {
+        let words: Vec<String> = name.split_whitespace().map(str::to_lowercase).collect();
+        self.groups
+            .iter()
+            .flat_map(|(group, recipients)| {
+                recipients.iter().map(move |recipient| (*group, recipient))
+            })
+            .filter(|(_group, recipient)| {
+                let recipient_name = recipient.name.to_lowercase();
+                words.iter().all(|word| recipient_name.contains(word))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnreadCounters {
+    pub mail: usize,
+    /// The announcements that were not opened yet.
+    pub announcements: usize,
+}
+
+#[derive(Serialize)]
+pub(crate) struct PageRequest {
+    pub skip: u32,
+    pub take: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForumPage<Item> {
+    pub items: Vec<Item>,
+    /// How many items there are on all the pages.
+    #[serde(rename(deserialize = "totalItems"))]
+    pub total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForumSection {
+    #[serde(with = "json::big_int")]
     pub id: BigInt,
     pub name: String,
+    pub description: Option<String>,
+    #[serde(rename(deserialize = "topicCount"), default)]
+    pub topic_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForumTopic {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    pub name: String,
+    /// The full name of the user who started the topic.
+    pub author: String,
+    #[serde(deserialize_with = "server_dates::date_time")]
+    pub created: NaiveDateTime,
+    #[serde(rename(deserialize = "messageCount"), default)]
+    pub message_count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForumMessage {
+    #[serde(with = "json::big_int")]
+    pub id: BigInt,
+    /// The full name of the user who posted the message.
+    pub author: String,
+    #[serde(deserialize_with = "server_dates::date_time")]
+    pub created: NaiveDateTime,
+    /// Can contain HTML.
+    pub text: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ForumMessageRequest<'forum_message_request> {
+    pub text: &'forum_message_request str,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ForumTopicRequest<'forum_topic_request> {
+    pub name: &'forum_topic_request str,
+    /// The text of the first message.
+    pub text: &'forum_topic_request str,
+}
+
+/// The timetable of the class. Unlike the diary, it lists all the lessons, including the ones
+/// without assignments.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Timetable {
+    #[serde(
+        rename(deserialize = "weekStart"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub week_start: NaiveDate,
+    #[serde(
+        rename(deserialize = "weekEnd"),
+        deserialize_with = "server_dates::date"
+    )]
+    pub week_end: NaiveDate,
+    pub days: Vec<TimetableDay>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TimetableDay {
+    #[serde(deserialize_with = "server_dates::date")]
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub lessons: Vec<TimetableLesson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TimetableLesson {
+    /// The number of the lesson in the day, starting from 1.
+    #[serde(with = "json::big_int")]
+    pub number: BigInt,
+    #[serde(rename(deserialize = "subjectName"))]
+    pub subject_name: String,
+    pub room: Option<String>,
+    /// The full name of the teacher.
+    pub teacher: Option<String>,
+    #[serde(
+        rename(deserialize = "startTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub start_time: NaiveTime,
+    #[serde(
+        rename(deserialize = "endTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub end_time: NaiveTime,
+}
+
+/// The lesson start and end times of a school. There can be several variants of them: for
+/// different days of the week and for the shortened days.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct BellSchedule {
+    pub variants: Vec<BellScheduleVariant>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BellScheduleVariant {
+    pub name: String,
+    /// The days of the week the variant is used on, from 1 (Monday) to 7 (Sunday). Empty if the
+    /// variant is used on every day that has no other variant.
+    #[serde(rename(deserialize = "daysOfWeek"), default)]
+    pub weekdays: Vec<u8>,
+    /// The specific dates the variant is used on (the shortened days, usually).
+    #[serde(deserialize_with = "server_dates::dates", default)]
+    pub dates: Vec<NaiveDate>,
+    #[serde(rename(deserialize = "isShortened"), default)]
+    pub is_shortened: bool,
+    pub lessons: Vec<LessonTime>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct LessonTime {
+    #[serde(with = "json::big_int")]
+    pub number: BigInt,
+    #[serde(
+        rename(deserialize = "startTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub start_time: NaiveTime,
+    #[serde(
+        rename(deserialize = "endTime"),
+        deserialize_with = "server_dates::time"
+    )]
+    pub end_time: NaiveTime,
+}
+
+/// Where a moment of a day is relative to the lessons.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LessonPosition<'bell_schedule> {
+    /// The lesson is going on.
+    During(&'bell_schedule LessonTime),
+    /// It's a break or the lessons have not started yet; this lesson is the next one.
+    Before(&'bell_schedule LessonTime),
+    /// All the lessons are over.
+    AfterLessons,
+}
+
+impl BellSchedule {
+    /// Picks the variant used on the `date`: the one listing the date itself, then the one for
+    /// the day of the week, then the default one.
+    pub fn for_date(&self, date: NaiveDate) -> Option<&BellScheduleVariant> {
+        let weekday = u8::try_from(date.weekday().number_from_monday()).unwrap();
+        self.variants
+            .iter()
+            .find(|variant| variant.dates.contains(&date))
+            .or_else(|| {
+                self.variants
+                    .iter()
+                    .find(|variant| variant.weekdays.contains(&weekday))
+            })
+            .or_else(|| {
+                self.variants
+                    .iter()
+                    .find(|variant| variant.weekdays.is_empty() && variant.dates.is_empty())
+            })
+    }
+
+    /// Finds the current or the next lesson at the moment.
+    pub fn lesson_at(&self, moment: NaiveDateTime) -> Option<LessonPosition<'_>> {
+        self.for_date(moment.date())
+            .map(|variant| variant.lesson_at(moment.time()))
+    }
+}
+
+impl BellScheduleVariant {
+    /// Finds the current or the next lesson at the `time`. A lesson is considered going on from
+    /// its start time up to (but not including) its end time.
+    pub fn lesson_at(&self, time: NaiveTime) -> LessonPosition<'_> {
+        if let Some(lesson) = self
+            .lessons
+            .iter()
+            .find(|lesson| lesson.start_time <= time && time < lesson.end_time)
+        {
+            return LessonPosition::During(lesson);
+        }
+        self.lessons
+            .iter()
+            .filter(|lesson| time < lesson.start_time)
+            .min_by_key(|lesson| lesson.start_time)
+            .map_or(LessonPosition::AfterLessons, LessonPosition::Before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::{BellSchedule, LessonPosition};
+
+    fn bell_schedule() -> BellSchedule {
+        serde_json::from_str(
+            r#"[
+                {
+                    "name": "Обычный",
+                    "lessons": [
+                        {"number": 1, "startTime": "08:30", "endTime": "09:15"},
+                        {"number": 2, "startTime": "09:25", "endTime": "10:10"}
+                    ]
+                },
+                {
+                    "name": "Суббота",
+                    "daysOfWeek": [6],
+                    "lessons": [{"number": 1, "startTime": "09:00", "endTime": "09:45"}]
+                },
+                {
+                    "name": "Сокращённый",
+                    "dates": ["2022-12-30T00:00:00"],
+                    "isShortened": true,
+                    "lessons": [{"number": 1, "startTime": "08:30", "endTime": "09:00"}]
+                }
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn variant_is_picked_by_date_then_weekday() {
+        let bell_schedule = bell_schedule();
+        let variant_name = |year, month, day| {
+            bell_schedule
+                .for_date(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+                .map(|variant| variant.name.as_str())
+        };
+        // A Friday, a Saturday and a shortened Friday.
+        assert_eq!(variant_name(2022, 12, 23), Some("Обычный"));
+        assert_eq!(variant_name(2022, 12, 24), Some("Суббота"));
+        assert_eq!(variant_name(2022, 12, 30), Some("Сокращённый"));
+    }
+
+    #[test]
+    fn lesson_at_finds_current_and_next_lessons() {
+        let bell_schedule = bell_schedule();
+        let variant = &bell_schedule.variants[0];
+        let describe = |position| match position {
+            LessonPosition::During(lesson) => format!("during {}", lesson.number),
+            LessonPosition::Before(lesson) => format!("before {}", lesson.number),
+            LessonPosition::AfterLessons => "after".to_owned(),
+        };
+        assert_eq!(describe(variant.lesson_at(time(7, 0))), "before 1");
+        assert_eq!(describe(variant.lesson_at(time(8, 30))), "during 1");
+        assert_eq!(describe(variant.lesson_at(time(9, 15))), "before 2");
+        assert_eq!(describe(variant.lesson_at(time(10, 9))), "during 2");
+        assert_eq!(describe(variant.lesson_at(time(10, 10))), "after");
+    }
 }
@@ -0,0 +1,36 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{de::Error, Deserialize, Deserializer};
+
+/// The server sends dates as date-times with a zero time ("2022-09-05T00:00:00"), so they have
+/// to be trimmed.
+pub fn date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&string, "%Y-%m-%dT%H:%M:%S%.f")
+        .map(|date_time| date_time.date())
+        .or_else(|_error| NaiveDate::parse_from_str(&string, "%Y-%m-%d"))
+        .map_err(D::Error::custom)
+}
+
+/// Same as `date`, but for a list of dates.
+pub fn dates<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<NaiveDate>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "date")] NaiveDate);
+
+    Ok(Vec::<Wrapper>::deserialize(deserializer)?
+        .into_iter()
+        .map(|Wrapper(date)| date)
+        .collect())
+}
+
+pub fn date_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&string, "%Y-%m-%dT%H:%M:%S%.f").map_err(D::Error::custom)
+}
+
+/// The server sends times without seconds ("08:30"), but sometimes with them.
+pub fn time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&string, "%H:%M")
+        .or_else(|_error| NaiveTime::parse_from_str(&string, "%H:%M:%S"))
+        .map_err(D::Error::custom)
+}
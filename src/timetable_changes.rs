@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use num::BigInt;
+
+use crate::schemas::{Timetable, TimetableLesson};
+
+/// A lesson with the day it is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledLesson {
+    pub date: NaiveDate,
+    pub lesson: TimetableLesson,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimetableChange {
+    Added(ScheduledLesson),
+    Cancelled(ScheduledLesson),
+    /// The lesson of the same subject has moved to another day or to another number. The room
+    /// and the teacher may have changed as well.
+    Moved {
+        from: ScheduledLesson,
+        to: ScheduledLesson,
+    },
+    RoomChanged {
+        lesson: ScheduledLesson,
+        previous_room: Option<String>,
+    },
+    TeacherSubstituted {
+        lesson: ScheduledLesson,
+        previous_teacher: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ComparisonError {
+    DifferentWeeks,
+}
+
+type Slot = (NaiveDate, BigInt);
+
+/// Several lessons can share a slot: the lessons of the class groups (two foreign languages at the
+/// same time, for example).
+fn slots(timetable: &Timetable) -> BTreeMap<Slot, Vec<ScheduledLesson>> {
+    let mut slots: BTreeMap<Slot, Vec<ScheduledLesson>> = BTreeMap::new();
+    for day in &timetable.days {
+        for lesson in &day.lessons {
+            slots
+                .entry((day.date, lesson.number.clone()))
+                .or_default()
+                .push(ScheduledLesson {
+                    date: day.date,
+                    lesson: lesson.clone(),
+                });
+        }
+    }
+    slots
+}
+
+impl TimetableChange {
+    /// Compares two fetches of the timetable of the same week. A lesson that is in the same
+    /// place in both of them is only checked for the room and the teacher changes. The removed
+    /// and the added lessons of the same subject are paired up into moves, the lessons on the
+    /// same day first; the rest of them are cancellations and additions. The changes are ordered
+    /// by the day and the number of the lesson.
+    pub fn between(
+        previous: &Timetable,
+        current: &Timetable,
+    ) -> Result<Vec<TimetableChange>, ComparisonError> {
+        if previous.week_start != current.week_start || previous.week_end != current.week_end {
+            return Err(ComparisonError::DifferentWeeks);
+        }
+        let mut previous_slots = slots(previous);
+        let mut changes = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for (slot, mut current_lessons) in slots(current) {
+            let mut previous_lessons = previous_slots.remove(&slot).unwrap_or_default();
+            // The lessons that have not changed at all are paired up first, so that the lessons
+            // of different groups in the same slot are not mixed up.
+            current_lessons.retain(|current_lesson| {
+                match previous_lessons
+                    .iter()
+                    .position(|previous_lesson| previous_lesson == current_lesson)
+                {
+                    Some(position) => {
+                        previous_lessons.remove(position);
+                        false
+                    }
+                    None => true,
+                }
+            });
+            for current_lesson in current_lessons {
+                let position = previous_lessons.iter().position(|previous_lesson| {
+                    previous_lesson.lesson.subject_name == current_lesson.lesson.subject_name
+                });
+                let previous_lesson = match position {
+                    Some(position) => previous_lessons.remove(position),
+                    None => {
+                        added.push(current_lesson);
+                        continue;
+                    }
+                };
+                if previous_lesson.lesson.room != current_lesson.lesson.room {
+                    changes.push(TimetableChange::RoomChanged {
+                        lesson: current_lesson.clone(),
+                        previous_room: previous_lesson.lesson.room.clone(),
+                    });
+                }
+                if previous_lesson.lesson.teacher != current_lesson.lesson.teacher {
+                    changes.push(TimetableChange::TeacherSubstituted {
+                        lesson: current_lesson,
+                        previous_teacher: previous_lesson.lesson.teacher,
+                    });
+                }
+            }
+            removed.extend(previous_lessons);
+        }
+        removed.extend(previous_slots.into_values().flatten());
+        removed.sort_by(|first, second| {
+            (first.date, &first.lesson.number).cmp(&(second.date, &second.lesson.number))
+        });
+
+        let mut added: Vec<Option<ScheduledLesson>> = added.into_iter().map(Some).collect();
+        for from in removed {
+            let same_subject = |lesson: &Option<ScheduledLesson>| {
+                lesson
+                    .as_ref()
+                    .is_some_and(|lesson| lesson.lesson.subject_name == from.lesson.subject_name)
+            };
+            let position = added
+                .iter()
+                .position(|lesson| {
+                    same_subject(lesson) && lesson.as_ref().unwrap().date == from.date
+                })
+                .or_else(|| added.iter().position(same_subject));
+            changes.push(match position {
+                Some(position) => TimetableChange::Moved {
+                    from,
+                    to: added[position].take().unwrap(),
+                },
+                None => TimetableChange::Cancelled(from),
+            });
+        }
+        changes.extend(added.into_iter().flatten().map(TimetableChange::Added));
+
+        changes.sort_by(|first, second| {
+            let first = first.lesson();
+            let second = second.lesson();
+            (first.date, &first.lesson.number).cmp(&(second.date, &second.lesson.number))
+        });
+        Ok(changes)
+    }
+
+    /// The lesson the change is about, in its new place if it has one.
+    pub fn lesson(&self) -> &ScheduledLesson {
+        match self {
+            TimetableChange::Added(lesson)
+            | TimetableChange::Cancelled(lesson)
+            | TimetableChange::Moved { to: lesson, .. }
+            | TimetableChange::RoomChanged { lesson, .. }
+            | TimetableChange::TeacherSubstituted { lesson, .. } => lesson,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use crate::schemas::{Timetable, TimetableDay, TimetableLesson};
+
+    use super::{ComparisonError, ScheduledLesson, TimetableChange};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 9, day).unwrap()
+    }
+
+    fn lesson(number: u8, subject_name: &str, room: &str, teacher: &str) -> TimetableLesson {
+        let start_time = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        TimetableLesson {
+            number: number.into(),
+            subject_name: subject_name.to_owned(),
+            room: Some(room.to_owned()),
+            teacher: Some(teacher.to_owned()),
+            start_time,
+            end_time: start_time,
+        }
+    }
+
+    fn scheduled(day: u32, lesson: TimetableLesson) -> ScheduledLesson {
+        ScheduledLesson {
+            date: date(day),
+            lesson,
+        }
+    }
+
+    /// A week starting on Monday, the 5th of September 2022.
+    fn week(days: Vec<(u32, Vec<TimetableLesson>)>) -> Timetable {
+        Timetable {
+            week_start: date(5),
+            week_end: date(11),
+            days: days
+                .into_iter()
+                .map(|(day, lessons)| TimetableDay {
+                    date: date(day),
+                    lessons,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn lessons_of_groups_in_the_same_slot_are_kept_apart() {
+        let english = lesson(3, "Английский язык", "21", "Иванова");
+        let german = lesson(3, "Немецкий язык", "22", "Петрова");
+        let previous = week(vec![(5, vec![english.clone(), german.clone()])]);
+
+        let swapped = week(vec![(5, vec![german.clone(), english.clone()])]);
+        assert_eq!(TimetableChange::between(&previous, &swapped).unwrap(), []);
+
+        let moved_german = lesson(3, "Немецкий язык", "30", "Петрова");
+        let current = week(vec![(5, vec![english.clone(), moved_german.clone()])]);
+        assert_eq!(
+            TimetableChange::between(&previous, &current).unwrap(),
+            [TimetableChange::RoomChanged {
+                lesson: scheduled(5, moved_german),
+                previous_room: Some("22".to_owned()),
+            }]
+        );
+
+        let current = week(vec![(5, vec![english])]);
+        assert_eq!(
+            TimetableChange::between(&previous, &current).unwrap(),
+            [TimetableChange::Cancelled(scheduled(5, german))]
+        );
+    }
+
+    #[test]
+    fn move_within_a_day() {
+        let physics = lesson(2, "Физика", "12", "Сидоров");
+        let later_physics = lesson(5, "Физика", "12", "Сидоров");
+        let previous = week(vec![(
+            5,
+            vec![lesson(1, "Химия", "7", "Орлова"), physics.clone()],
+        )]);
+        let current = week(vec![(
+            5,
+            vec![lesson(1, "Химия", "7", "Орлова"), later_physics.clone()],
+        )]);
+        assert_eq!(
+            TimetableChange::between(&previous, &current).unwrap(),
+            [TimetableChange::Moved {
+                from: scheduled(5, physics),
+                to: scheduled(5, later_physics),
+            }]
+        );
+    }
+
+    #[test]
+    fn move_across_days_and_other_changes() {
+        let physics = lesson(2, "Физика", "12", "Сидоров");
+        let history = lesson(4, "История", "15", "Кузнецов");
+        let previous = week(vec![(5, vec![physics.clone()]), (6, vec![history.clone()])]);
+        let moved_physics = lesson(3, "Физика", "12", "Сидоров");
+        let art = lesson(1, "ИЗО", "3", "Смирнова");
+        let current = week(vec![(6, vec![art.clone(), moved_physics.clone()])]);
+        assert_eq!(
+            TimetableChange::between(&previous, &current).unwrap(),
+            [
+                TimetableChange::Added(scheduled(6, art)),
+                TimetableChange::Moved {
+                    from: scheduled(5, physics),
+                    to: scheduled(6, moved_physics),
+                },
+                TimetableChange::Cancelled(scheduled(6, history)),
+            ]
+        );
+    }
+
+    #[test]
+    fn room_change_and_teacher_substitution() {
+        let previous = week(vec![(7, vec![lesson(1, "Алгебра", "10", "Волкова")])]);
+        let changed = lesson(1, "Алгебра", "11", "Морозов");
+        let current = week(vec![(7, vec![changed.clone()])]);
+        assert_eq!(
+            TimetableChange::between(&previous, &current).unwrap(),
+            [
+                TimetableChange::RoomChanged {
+                    lesson: scheduled(7, changed.clone()),
+                    previous_room: Some("10".to_owned()),
+                },
+                TimetableChange::TeacherSubstituted {
+                    lesson: scheduled(7, changed),
+                    previous_teacher: Some("Волкова".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn different_weeks_are_not_compared() {
+        let previous = week(Vec::new());
+        let mut current = week(Vec::new());
+        current.week_start = date(12);
+        current.week_end = date(18);
+        assert!(matches!(
+            TimetableChange::between(&previous, &current),
+            Err(ComparisonError::DifferentWeeks)
+        ));
+    }
+}
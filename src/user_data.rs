@@ -1,15 +1,22 @@
 use std::collections::HashMap;
 
 use num::BigInt;
+use serde::de::DeserializeOwned;
 
 use crate::{
     auth_data::AuthData,
-    schemas::{Assignment, CurrentYear, DiaryInfo},
-    web_clients::{logged_in_web_client::LoggedInWebClient, web_client_wrapper::WebClientWrapper},
+    schemas::{AssignmentType, CurrentYear, DiaryInfo, Student},
+    web_clients::{
+        logged_in_web_client::LoggedInWebClient, request_builder::RequestBuilder,
+        web_client_wrapper::WebClientWrapper,
+    },
 };
 
 pub struct UserData<Username, Password> {
+    /// The id of the student whose data is requested.
     student_id: BigInt,
+    /// All the students available to the user (several for a parent with more than one child).
+    students: Vec<Student>,
     year_id: BigInt,
     assignment_types: HashMap<BigInt, String>,
     auth_data: AuthData<Username, Password>,
@@ -21,79 +28,149 @@ pub enum MakingError {
     InvalidJsonInServerResponse,
 }
 
+/// Tells that the school year has changed since the user data was made (or last refreshed).
+#[derive(Debug)]
+pub struct YearChange {
+    pub previous_year_id: BigInt,
+    pub current_year_id: BigInt,
+}
+
+pub(crate) async fn receive_json<T: DeserializeOwned>(
+    request: RequestBuilder,
+) -> Result<T, MakingError> {
+    match request.send().await {
+        Ok(resp) => resp
+            .json()
+            .await
+            .or(Err(MakingError::InvalidJsonInServerResponse)),
+        Err(error) => Err(MakingError::RequestError(error)),
+    }
+}
+
+/// The part of the user data that has to be received again when the school year changes.
+struct YearData {
+    student_id: BigInt,
+    students: Vec<Student>,
+    assignment_types: HashMap<BigInt, String>,
+}
+
+impl YearData {
+    /// Selects the student with the `preferred_student_id` if they are available, and the one the
+    /// server selects by default otherwise.
+    async fn receive(
+        web_client: &WebClientWrapper<LoggedInWebClient>,
+        preferred_student_id: Option<&BigInt>,
+    ) -> Result<Self, MakingError> {
+        let diary_info: DiaryInfo =
+            receive_json(web_client.get("student/diary/init").unwrap()).await?;
+        let student_id = match preferred_student_id
+            .filter(|&preferred_student_id| {
+                diary_info
+                    .students
+                    .iter()
+                    .any(|student| &student.student_id == preferred_student_id)
+            })
+            .or_else(|| {
+                diary_info
+                    .students
+                    .iter()
+                    .map(|student| &student.student_id)
+                    .find(|&student_id| student_id == &diary_info.current_student_id)
+            }) {
+            Some(student_id) => student_id.clone(),
+            None => return Err(MakingError::InvalidJsonInServerResponse),
+        };
+
+        let assignment_types: Vec<AssignmentType> =
+            receive_json(web_client.get("grade/assignment/types").unwrap()).await?;
+
+        Ok(Self {
+            student_id,
+            students: diary_info.students,
+            assignment_types: assignment_types
+                .into_iter()
+                .map(|assignment_type| (assignment_type.id, assignment_type.name))
+                .collect(),
+        })
+    }
+}
+
 impl<Username: Send, Password: Send> UserData<Username, Password> {
     pub async fn make(
         web_client: &WebClientWrapper<LoggedInWebClient>,
         auth_data: AuthData<Username, Password>,
     ) -> Result<Self, (MakingError, AuthData<Username, Password>)> {
-        macro_rules! error {
-            ($error:expr) => {
-                return Err(($error, auth_data))
-            };
-        }
-
-        macro_rules! to_json {
-            ($response:expr) => {
-                match $response {
-                    Ok(resp) => match resp.json().await {
-                        Ok(json) => json,
-                        Err(_parsing_error) => error!(MakingError::InvalidJsonInServerResponse),
-                    },
-                    Err(error) => error!(MakingError::RequestError(error)),
+        macro_rules! try_receive {
+            ($receiving:expr) => {
+                match $receiving.await {
+                    Ok(received) => received,
+                    Err(error) => return Err((error, auth_data)),
                 }
             };
         }
 
-        let student = {
-            let mut diary_info: DiaryInfo =
-                match web_client.get("student/diary/init").unwrap().send().await {
-                    Ok(resp) => match resp.json().await {
-                        Ok(json) => json,
-                        Err(_parsing_error) => error!(MakingError::InvalidJsonInServerResponse),
-                    },
-                    Err(error) => error!(MakingError::RequestError(error)),
-                };
-            let student_index = match diary_info.current_student_id.try_into() {
-                Ok(index) => index,
-                Err(_conversion_error) => error!(MakingError::InvalidJsonInServerResponse),
-            };
-            if student_index >= diary_info.students.len() {
-                error!(MakingError::InvalidJsonInServerResponse);
-            }
-            diary_info.students.swap_remove(student_index)
-        };
-
         let current_year: CurrentYear =
-            to_json!(web_client.get("years/current").unwrap().send().await);
-
-        let assignment_types: HashMap<BigInt, String> = {
-            let assignments: Vec<Assignment> = to_json!(
-                web_client
-                    .get("grade/assignment/types")
-                    .unwrap()
-                    .send()
-                    .await
-            );
-            assignments
-                .into_iter()
-                .map(|assignment| (assignment.id, assignment.name))
-                .collect()
-        };
+            try_receive!(receive_json(web_client.get("years/current").unwrap()));
+        let year_data = try_receive!(YearData::receive(web_client, None));
 
         Ok(Self {
             auth_data,
-            student_id: student.student_id,
-            assignment_types,
+            student_id: year_data.student_id,
+            students: year_data.students,
+            assignment_types: year_data.assignment_types,
             year_id: current_year.id,
         })
     }
 }
 
 impl<Username, Password> UserData<Username, Password> {
+    /// Checks whether the current school year has changed and, if it has, updates everything that
+    /// depends on it (the year itself, the students list and the assignment types). The
+    /// previously chosen student is kept if they are still available.
+    pub async fn refresh_year(
+        &mut self,
+        web_client: &WebClientWrapper<LoggedInWebClient>,
+    ) -> Result<Option<YearChange>, MakingError> {
+        let current_year: CurrentYear =
+            receive_json(web_client.get("years/current").unwrap()).await?;
+        if current_year.id == self.year_id {
+            return Ok(None);
+        }
+
+        let year_data = YearData::receive(web_client, Some(&self.student_id)).await?;
+        self.student_id = year_data.student_id;
+        self.students = year_data.students;
+        self.assignment_types = year_data.assignment_types;
+        let previous_year_id = std::mem::replace(&mut self.year_id, current_year.id);
+        Ok(Some(YearChange {
+            previous_year_id,
+            current_year_id: self.year_id.clone(),
+        }))
+    }
+
     pub const fn student_id(&self) -> &BigInt {
         &self.student_id
     }
 
+    pub fn students(&self) -> &[Student] {
+        &self.students
+    }
+
+    /// Makes the student with the specified id the one whose data is requested. Returns `false`
+    /// (changing nothing) if the user has no access to such a student.
+    pub fn switch_student(&mut self, student_id: &BigInt) -> bool {
+        if self
+            .students
+            .iter()
+            .any(|student| &student.student_id == student_id)
+        {
+            self.student_id = student_id.clone();
+            true
+        } else {
+            false
+        }
+    }
+
     pub const fn year_id(&self) -> &BigInt {
         &self.year_id
     }
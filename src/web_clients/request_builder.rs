@@ -15,6 +15,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn multipart(mut self, form: reqwest::multipart::Form) -> Self {
+        self.0 = self.0.multipart(form);
+        self
+    }
+
     pub const fn new(inner: reqwest::RequestBuilder) -> Self {
         Self(inner)
     }